@@ -1,17 +1,89 @@
 complete -c rucat -s f -l format -d 'Output format' -r -f -a "ansi\t'ANSI box drawing characters'
 xml\t'XML format'
-json\t'JSON format'
+json\t'JSON document (see --json-schema)'
+jsonl\t'JSON Lines, one object per file, streamed as files are read'
+yaml\t'YAML with the fields of the JSON document, code in literal blocks'
+toml\t'TOML with the fields of the JSON document, code in multi-line strings'
 markdown\t'Markdown code blocks'
 ascii\t'Simple ASCII header'
 utf8\t'Fancy UTF-8 box drawing'
-pretty\t'Pretty-printed with syntax highlighting'"
-complete -c rucat -l ansi-width -d 'Width for ANSI formatting (excluding borders)' -r
-complete -c rucat -l utf8-width -d 'Width for UTF8 formatting (excluding borders)' -r
+pretty\t'Pretty-printed with syntax highlighting'
+html\t'Self-contained HTML page with an index and highlighted files'
+template\t'User-defined template (see --template)'
+documents\t'`<documents>` XML for long-context prompts'
+messages\t'Chat API request body (see --messages-api)'"
+complete -c rucat -l ansi-width -d 'Width for ANSI formatting (excluding borders); defaults to the terminal width' -r
+complete -c rucat -l utf8-width -d 'Width for UTF8 formatting (excluding borders); defaults to the terminal width' -r
+complete -c rucat -l tabs -d 'Expand tabs to stops every N columns in the \'ansi\' and \'utf8\' formats (0 keeps tabs)' -r
+complete -c rucat -l wrap -d 'Long lines in the \'ansi\' and \'utf8\' formats: wrap, truncate, or widen the box' -r -f -a "wrap\t'Continue long lines on the next row'
+truncate\t'Cut long lines off with `…`'
+never\t'Widen the box to fit the longest line'"
+complete -c rucat -l meta -d 'Show file metadata (size, mtime, lines, lang, sha256, mode) with each file' -r -f -a "size\t'Size in bytes'
+mtime\t'Last modification time (RFC 3339, UTC)'
+lines\t'Number of lines'
+lang\t'Language, as detected from the file name'
+sha256\t'SHA-256 digest of the contents'
+mode\t'Permission bits in octal (Unix only)'"
 complete -c rucat -l strip -d 'Remove N leading path components when printing filenames' -r
-complete -c rucat -l pretty-syntax -d 'Explicitly set the syntax for the \'pretty\' formatter' -r
+complete -c rucat -l markdown-fence -d 'Fence character of the \'markdown\' format (fences grow past any run in the content)' -r -f -a "backticks\t'``` fences'
+tildes\t'~~~ fences'"
+complete -c rucat -l markdown-header -d 'File header style of the \'markdown\' format' -r -f -a "front-matter\t'`---` / `File: path` / `---`'
+heading\t'`### path` heading'
+bold\t'`**path**` on a line of its own'"
+complete -c rucat -l html-style -d 'Highlighting of the \'html\' format: inline styles, or classes and one stylesheet' -r -f -a "inline\t'`style` attributes on every token'
+classes\t'`class` attributes and the theme as a `<style>` sheet'"
+complete -c rucat -l messages-api -d 'Request body flavour of the \'messages\' format' -r -f -a "openai\t'OpenAI-compatible chat completions (`messages` with a system message)'
+anthropic\t'Anthropic Messages API (top-level `system` and `max_tokens`)'"
+complete -c rucat -l messages-content -d 'Put each file in its own content block, or everything in one text block' -r -f -a "files\t'One content block per file'
+single\t'A single text block holding the whole bundle'"
+complete -c rucat -l system -d 'System prompt for the \'messages\' format' -r
+complete -c rucat -l model -d 'Model name for the \'messages\' format' -r
+complete -c rucat -l pretty-syntax -d 'Explicitly set the syntax for the \'pretty\', \'ansi\' and \'html\' formatters' -r
+complete -c rucat -l decorations -d 'What the \'pretty\' format draws around each file: header and grid, header only, or nothing' -r -f -a "full\t'A file header and grid borders around the contents'
+header\t'A file header only'
+plain\t'The highlighted contents alone'"
+complete -c rucat -l paging -d 'When to page output through $RUCAT_PAGER, $PAGER or \'less -R\': \'auto\' pages output longer than the terminal' -r -f -a "auto\t'Page output that is longer than the terminal'
+always\t'Always page output'
+never\t'Never page output'"
+complete -c rucat -l color -d 'When to use colours: only on a terminal (\'auto\'), \'always\' or \'never\'' -r -f -a "auto\t'Colours when writing to a terminal, unless `NO_COLOR` is set'
+always\t'Always use colours'
+never\t'Never use colours'"
+complete -c rucat -l theme -d 'Highlighting theme of the \'pretty\', \'html\' and highlighted \'ansi\' formats, or \'auto\' to follow the terminal background' -r
+complete -c rucat -l stats -d 'Print file, line, byte and token statistics (to stderr, as an output footer, or as JSON on stderr)' -r -f -a "stderr\t'Human-readable summary on stderr'
+footer\t'Summary appended to the output in the format\'s own style'
+json\t'JSON object on stderr, for scripts'"
+complete -c rucat -l secrets -d 'What to do with secrets (keys, tokens, passwords) found in the input' -r -f -a "redact\t'Replace secrets with [REDACTED:kind] markers and report them on stderr'
+warn\t'Leave secrets in place but report them on stderr, and refuse to --copy them'
+refuse\t'Exit with an error if any secret is found'
+off\t'Do not scan for secrets'"
+complete -c rucat -l secrets-allowlist -d 'File of regular expressions (one per line) for secrets that may be printed' -r -F
+complete -c rucat -l rewrite -d 'Rewrite file contents and paths with a sed-style s/PATTERN/REPLACEMENT/ rule (repeatable)' -r
+complete -c rucat -l prompt -d 'Put TEXT, or the named prompt from the prompts directory, around the output' -r
+complete -c rucat -l prompt-file -d 'Put the contents of FILE around the output' -r -F
+complete -c rucat -l template -d 'Format the output with the template in FILE (implies --format template)' -r -F
+complete -c rucat -l budget -d 'Fit the output into a token budget (e.g. 100k, 1.5m or claude-200k)' -r
+complete -c rucat -l split -d 'Break the output into parts of at most SIZE tokens (e.g. 30k) or bytes (e.g. 60kb)' -r
+complete -c rucat -l split-dir -d 'With --split, write the parts to files in DIR instead of stdout' -r -F
+complete -c rucat -s o -l output -d 'Write the output to FILE instead of stdout, replacing it only once complete' -r -F
+complete -c rucat -l output-dir -d 'Write each file\'s output to its own file under DIR, mirroring its path, with an index.md' -r -F
+complete -c rucat -l must -d 'Never drop PATH (a file or directory) when applying --budget' -r -F
+complete -c rucat -l tree-info -d 'Annotate the --tree overview with file sizes or line counts (implies --tree)' -r -f -a "none\t'File and directory names only'
+size\t'Size in bytes'
+lines\t'Number of lines'"
 complete -c rucat -l clipboard-provider-for-test -d 'FOR TESTING ONLY: Force a specific clipboard provider' -r
+complete -c rucat -l ansi-highlight -d 'Highlight file contents inside the \'ansi\' boxes'
 complete -c rucat -s n -l numbers -d 'Add a gutter with line numbers'
 complete -c rucat -s 0 -l null -d 'Read NUL-terminated file list from STDIN (like `xargs -0`)'
+complete -c rucat -l xml-cdata -d 'Put file contents of the \'xml\' format in CDATA sections instead of escaping them'
+complete -c rucat -l documents-metadata -d 'Add language, line and byte counts to each document of the \'documents\' format'
+complete -c rucat -l list-themes -d 'List the available highlighting themes and exit'
+complete -c rucat -l list-syntaxes -d 'List the available syntaxes and their file extensions and exit'
+complete -c rucat -l strip-comments -d 'Remove comments and license headers (doc comments are kept)'
+complete -c rucat -l strip-doc-comments -d 'With --strip-comments, remove doc comments too'
+complete -c rucat -l minify-whitespace -d 'Trim trailing whitespace and collapse runs of blank lines'
+complete -c rucat -l tee -d 'With --output, print the output to stdout as well'
+complete -c rucat -l json-schema -d 'Print the JSON Schema of the \'json\' and \'jsonl\' formats and exit'
+complete -c rucat -l tree -d 'Print a directory tree of the collected files before their contents'
 complete -c rucat -s c -l copy -d 'Copy output to the system clipboard'
 complete -c rucat -s h -l help -d 'Print help (see more with \'--help\')'
 complete -c rucat -s V -l version -d 'Print version'
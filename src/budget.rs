@@ -0,0 +1,188 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+/// Named context windows accepted by `--budget` in place of a number.
+pub const PRESETS: &[(&str, usize)] = &[
+    ("claude-200k", 200_000),
+    ("claude-1m", 1_000_000),
+    ("gpt-4o", 128_000),
+    ("gpt-4.1", 1_000_000),
+    ("gpt-4-32k", 32_768),
+    ("gemini-1m", 1_000_000),
+    ("gemini-2m", 2_000_000),
+    ("llama-8k", 8_192),
+    ("llama-128k", 128_000),
+];
+
+/// Tokens set aside for the marker [`truncate`] appends.
+const MARKER: usize = 16;
+
+/// Files are only truncated when at least this many tokens remain for them;
+/// below that a stub is less useful than leaving the file out entirely.
+const MIN_TRUNCATE: usize = 64;
+
+/// Parse a `--budget` value: a plain number (`120000`), a number with a
+/// `k`/`m` suffix and at most one decimal (`100k`, `1.5m`) or one of the
+/// [`PRESETS`].
+///
+/// # Errors
+///
+/// Returns an error message if the value is neither a number of at least
+/// one nor a preset.
+pub fn parse_budget(s: &str) -> Result<usize, String> {
+    let lower = s.trim().to_ascii_lowercase();
+    if let Some((_, n)) = PRESETS.iter().find(|(name, _)| *name == lower) {
+        return Ok(*n);
+    }
+    let (number, scale) = match lower.chars().last() {
+        Some('k') => (&lower[..lower.len() - 1], 1_000),
+        Some('m') => (&lower[..lower.len() - 1], 1_000_000),
+        _ => (lower.as_str(), 1),
+    };
+    // A decimal only makes sense with a suffix: `1.5m`, but not `0.1`.
+    let (whole, tenths) = match number.split_once('.') {
+        Some((whole, tenths)) if scale > 1 && tenths.len() == 1 => (whole, tenths),
+        Some(_) => ("", ""),
+        None => (number, "0"),
+    };
+    let digits = |part: &str| {
+        (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| part.parse::<usize>().ok())
+            .flatten()
+    };
+    digits(whole)
+        .zip(digits(tenths))
+        .and_then(|(whole, tenths)| whole.checked_mul(scale)?.checked_add(tenths * scale / 10))
+        .filter(|&n| n >= 1)
+        .ok_or_else(|| {
+            let names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+            format!(
+                "Invalid budget '{s}'. Use a token count such as 100k or one of: {}",
+                names.join(", ")
+            )
+        })
+}
+
+/// Rough token estimate (about four bytes per token), good enough to plan a
+/// bundle without depending on a particular model's tokenizer.
+#[must_use]
+pub const fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// A file competing for room in the budget.
+pub struct Candidate<'a> {
+    pub content: &'a str,
+    /// Lower ranks win; rucat uses the position of the argument the file came from.
+    pub rank: usize,
+    /// Files named with `--must` are always kept whole.
+    pub must: bool,
+    /// Tokens the file adds to the rendered output.
+    pub cost: usize,
+    /// Tokens it would add with no content: its header, fences and separators.
+    pub overhead: usize,
+}
+
+/// What to do with a candidate once the budget has been planned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Keep,
+    /// Keep only the first N bytes (cut at a line boundary).
+    Truncate(usize),
+    Omit,
+}
+
+/// Decide which candidates fit into `limit` tokens.
+///
+/// Must-have files go first, then files in rank order, smallest first within
+/// a rank, skipping files that no longer fit. The room that is left then
+/// goes to truncated copies of the skipped files, in the same order. The
+/// returned verdicts are in the same order as `candidates`.
+///
+/// # Errors
+///
+/// Returns an error if the must-have files alone exceed the budget.
+pub fn plan(candidates: &[Candidate], limit: usize) -> anyhow::Result<Vec<Verdict>> {
+    let must: usize = candidates.iter().filter(|c| c.must).map(|c| c.cost).sum();
+    if must > limit {
+        anyhow::bail!(
+            "must-have files need about {must} tokens but only {limit} tokens are left for files"
+        );
+    }
+
+    let mut order: Vec<usize> = (0..candidates.len()).collect();
+    order.sort_by_key(|&i| {
+        let c = &candidates[i];
+        (!c.must, c.rank, c.content.len())
+    });
+
+    let mut verdicts = vec![Verdict::Omit; candidates.len()];
+    let mut remaining = limit;
+    let mut left_out = Vec::new();
+    for i in order {
+        let c = &candidates[i];
+        if c.cost <= remaining {
+            verdicts[i] = Verdict::Keep;
+            remaining -= c.cost;
+        } else {
+            left_out.push(i);
+        }
+    }
+    for i in left_out {
+        if remaining >= MIN_TRUNCATE
+            && let Some((keep, cost)) = truncation(&candidates[i], remaining)
+        {
+            verdicts[i] = Verdict::Truncate(keep);
+            remaining -= cost;
+        }
+    }
+    Ok(verdicts)
+}
+
+/// The longest prefix of `c`, cut at a line boundary, that fits into `room`
+/// tokens along with its overhead and the truncation marker, and what it
+/// costs. Contents are assumed to cost the same per byte throughout.
+fn truncation(c: &Candidate, room: usize) -> Option<(usize, usize)> {
+    let len = c.content.len();
+    let body = c.cost.saturating_sub(c.overhead).max(1);
+    let fixed = c.overhead + MARKER;
+    let bytes = len * room.checked_sub(fixed)? / body;
+    let keep = line_boundary(c.content, bytes);
+    (keep > 0).then(|| (keep, fixed + (body * keep).div_ceil(len)))
+}
+
+/// Largest prefix length of `content` no longer than `max` bytes that ends
+/// on a line boundary.
+fn line_boundary(content: &str, max: usize) -> usize {
+    if max >= content.len() {
+        return content.len();
+    }
+    content.as_bytes()[..max]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1)
+}
+
+/// Cut `content` to `keep` bytes and append a marker saying how much is missing.
+#[must_use]
+pub fn truncate(content: &str, keep: usize) -> String {
+    let shown = content[..keep].lines().count();
+    let total = content.lines().count();
+    format!(
+        "{}[... truncated by rucat: {shown} of {total} lines shown ...]\n",
+        &content[..keep]
+    )
+}
@@ -1,22 +1,36 @@
 use clap::Parser;
 use serde::Deserialize;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Args {
     /// Output format
     #[arg(short, long, value_enum)]
     pub format: Option<OutputFormat>,
 
-    /// Width for ANSI formatting (excluding borders)
+    /// Width for ANSI formatting (excluding borders); defaults to the terminal width
     #[arg(long)]
     pub ansi_width: Option<usize>,
 
-    /// Width for UTF8 formatting (excluding borders)
+    /// Highlight file contents inside the 'ansi' boxes
+    #[arg(long)]
+    pub ansi_highlight: bool,
+
+    /// Width for UTF8 formatting (excluding borders); defaults to the terminal width
     #[arg(long)]
     pub utf8_width: Option<usize>,
 
+    /// Expand tabs to stops every N columns in the 'ansi' and 'utf8' formats (0 keeps tabs)
+    #[arg(long, value_name = "N")]
+    pub tabs: Option<usize>,
+
+    /// Long lines in the 'ansi' and 'utf8' formats: wrap, truncate, or widen the box
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub wrap: Option<LineWrap>,
+
     /// Add a gutter with line numbers
     #[arg(short = 'n', long = "numbers")]
     pub line_numbers: bool,
@@ -25,14 +39,158 @@ pub struct Args {
     #[arg(short = '0', long = "null")]
     pub null_sep: bool,
 
+    /// Show file metadata (size, mtime, lines, lang, sha256, mode) with each file
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELDS")]
+    pub meta: Vec<MetaField>,
+
     /// Remove N leading path components when printing filenames
     #[arg(long, value_name = "N")]
     pub strip: Option<usize>,
 
-    /// Explicitly set the syntax for the 'pretty' formatter
+    /// Fence character of the 'markdown' format (fences grow past any run in the content)
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub markdown_fence: Option<MarkdownFence>,
+
+    /// File header style of the 'markdown' format
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub markdown_header: Option<MarkdownHeader>,
+
+    /// Put file contents of the 'xml' format in CDATA sections instead of escaping them
+    #[arg(long)]
+    pub xml_cdata: bool,
+
+    /// Highlighting of the 'html' format: inline styles, or classes and one stylesheet
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub html_style: Option<HtmlStyle>,
+
+    /// Add language, line and byte counts to each document of the 'documents' format
+    #[arg(long)]
+    pub documents_metadata: bool,
+
+    /// Request body flavour of the 'messages' format
+    #[arg(long, value_enum, value_name = "API")]
+    pub messages_api: Option<MessagesApi>,
+
+    /// Put each file in its own content block, or everything in one text block
+    #[arg(long, value_enum, value_name = "LAYOUT")]
+    pub messages_content: Option<MessagesContent>,
+
+    /// System prompt for the 'messages' format
+    #[arg(long, value_name = "TEXT")]
+    pub system: Option<String>,
+
+    /// Model name for the 'messages' format
+    #[arg(long, value_name = "NAME")]
+    pub model: Option<String>,
+
+    /// Explicitly set the syntax for the 'pretty', 'ansi' and 'html' formatters
     #[arg(long)]
     pub pretty_syntax: Option<String>,
 
+    /// What the 'pretty' format draws around each file: header and grid, header only, or nothing
+    #[arg(long, value_enum, value_name = "STYLE")]
+    pub decorations: Option<Decorations>,
+
+    /// When to page output through $RUCAT_PAGER, $PAGER or 'less -R': 'auto' pages output longer than the terminal
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub paging: Option<Paging>,
+
+    /// When to use colours: only on a terminal ('auto'), 'always' or 'never'
+    #[arg(long, value_enum, value_name = "WHEN")]
+    pub color: Option<ColorMode>,
+
+    /// Highlighting theme of the 'pretty', 'html' and highlighted 'ansi' formats, or 'auto' to follow the terminal background
+    #[arg(long, value_name = "NAME")]
+    pub theme: Option<String>,
+
+    /// List the available highlighting themes and exit
+    #[arg(long)]
+    pub list_themes: bool,
+
+    /// List the available syntaxes and their file extensions and exit
+    #[arg(long)]
+    pub list_syntaxes: bool,
+
+    /// Print file, line, byte and token statistics (to stderr, as an output footer, or as JSON on stderr)
+    #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, default_missing_value = "stderr")]
+    pub stats: Option<StatsMode>,
+
+    /// What to do with secrets (keys, tokens, passwords) found in the input
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub secrets: Option<SecretsMode>,
+
+    /// File of regular expressions (one per line) for secrets that may be printed
+    #[arg(long, value_name = "FILE")]
+    pub secrets_allowlist: Option<PathBuf>,
+
+    /// Rewrite file contents and paths with a sed-style s/PATTERN/REPLACEMENT/ rule (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub rewrite: Vec<String>,
+
+    /// Remove comments and license headers (doc comments are kept)
+    #[arg(long)]
+    pub strip_comments: bool,
+
+    /// With --strip-comments, remove doc comments too
+    #[arg(long)]
+    pub strip_doc_comments: bool,
+
+    /// Trim trailing whitespace and collapse runs of blank lines
+    #[arg(long)]
+    pub minify_whitespace: bool,
+
+    /// Put TEXT, or the named prompt from the prompts directory, around the output
+    #[arg(long, value_name = "TEXT|NAME", conflicts_with = "prompt_file")]
+    pub prompt: Option<String>,
+
+    /// Put the contents of FILE around the output
+    #[arg(long, value_name = "FILE")]
+    pub prompt_file: Option<PathBuf>,
+
+    /// Format the output with the template in FILE (implies --format template)
+    #[arg(long, value_name = "FILE")]
+    pub template: Option<PathBuf>,
+
+    /// Fit the output into a token budget (e.g. 100k, 1.5m or claude-200k)
+    #[arg(long, value_name = "TOKENS")]
+    pub budget: Option<String>,
+
+    /// Break the output into parts of at most SIZE tokens (e.g. 30k) or bytes (e.g. 60kb)
+    #[arg(long, value_name = "SIZE")]
+    pub split: Option<String>,
+
+    /// With --split, write the parts to files in DIR instead of stdout
+    #[arg(long, value_name = "DIR")]
+    pub split_dir: Option<PathBuf>,
+
+    /// Write the output to FILE instead of stdout, replacing it only once complete
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// With --output, print the output to stdout as well
+    #[arg(long)]
+    pub tee: bool,
+
+    /// Write each file's output to its own file under DIR, mirroring its path, with an index.md
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+
+    /// Never drop PATH (a file or directory) when applying --budget
+    #[arg(long, value_name = "PATH")]
+    pub must: Vec<PathBuf>,
+
+    /// Print the JSON Schema of the 'json' and 'jsonl' formats and exit
+    #[arg(long)]
+    pub json_schema: bool,
+
+    /// Print a directory tree of the collected files before their contents
+    #[arg(long)]
+    pub tree: bool,
+
+    /// Annotate the --tree overview with file sizes or line counts (implies --tree)
+    #[arg(long, value_enum, value_name = "INFO")]
+    pub tree_info: Option<TreeInfo>,
+
     /// Copy output to the system clipboard
     #[cfg(feature = "clipboard")]
     #[arg(short, long)]
@@ -57,6 +215,32 @@ pub struct Args {
     pub files: Vec<PathBuf>,
 }
 
+/// Modes `--stats` accepts; without one it reports on stderr.
+const STATS_MODES: [&str; 3] = ["stderr", "footer", "json"];
+
+/// `raw` with `stderr` after each `--stats` that is not followed by a mode.
+/// clap would otherwise take a path after the flag as its value.
+fn with_stats_mode(raw: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut out = Vec::new();
+    let mut raw = raw.peekable();
+    while let Some(arg) = raw.next() {
+        let end = arg == "--";
+        let bare = arg == "--stats"
+            && !raw
+                .peek()
+                .is_some_and(|next| STATS_MODES.iter().any(|m| next == m));
+        out.push(arg);
+        if bare {
+            out.push(OsString::from("stderr"));
+        }
+        if end {
+            out.extend(raw);
+            break;
+        }
+    }
+    out
+}
+
 impl Args {
     /// Parse arguments, handling trailing flags after files
     ///
@@ -68,7 +252,7 @@ impl Args {
     /// - A flag value is invalid (e.g., non-numeric value for numeric flags)
     /// - The clipboard feature is not enabled but clipboard flags are used
     pub fn parse_with_trailing() -> Result<Self, String> {
-        let mut args = Self::parse();
+        let mut args = Self::parse_from(with_stats_mode(std::env::args_os()));
 
         // Clone raw_args to avoid borrow checker issues
         let raw_args = args.raw_args.clone();
@@ -101,27 +285,70 @@ impl Args {
 
         match arg.as_str() {
             "-c" | "--copy" => Self::handle_copy_flag(args, arg, i),
-            "-n" | "--numbers" => {
-                args.line_numbers = true;
-                *i += 1;
-                Ok(true)
-            }
-            "-0" | "--null" => {
-                args.null_sep = true;
-                *i += 1;
-                Ok(true)
-            }
+            "-n" | "--numbers" => Self::handle_bool_flag(i, &mut args.line_numbers),
+            "-0" | "--null" => Self::handle_bool_flag(i, &mut args.null_sep),
             "-f" | "--format" => Self::handle_format_flag(args, raw_args, i),
             "--strip" => Self::handle_numeric_flag(raw_args, i, |n| args.strip = Some(n), "strip"),
             "--ansi-width" => {
                 Self::handle_numeric_flag(raw_args, i, |n| args.ansi_width = Some(n), "ansi-width")
             }
+            "--ansi-highlight" => Self::handle_bool_flag(i, &mut args.ansi_highlight),
             "--utf8-width" => {
                 Self::handle_numeric_flag(raw_args, i, |n| args.utf8_width = Some(n), "utf8-width")
             }
+            "--meta" => Self::handle_meta_flag(args, raw_args, i),
+            "--markdown-fence" => Self::handle_markdown_fence_flag(args, raw_args, i),
+            "--markdown-header" => Self::handle_markdown_header_flag(args, raw_args, i),
+            "--xml-cdata" => Self::handle_bool_flag(i, &mut args.xml_cdata),
+            "--html-style" => Self::handle_html_style_flag(args, raw_args, i),
+            "--documents-metadata" => Self::handle_bool_flag(i, &mut args.documents_metadata),
+            "--messages-api" => Self::handle_messages_api_flag(args, raw_args, i),
+            "--messages-content" => Self::handle_messages_content_flag(args, raw_args, i),
+            "--system" => Self::handle_string_flag(raw_args, i, |s| args.system = Some(s)),
+            "--model" => Self::handle_string_flag(raw_args, i, |s| args.model = Some(s)),
             "--pretty-syntax" => {
                 Self::handle_string_flag(raw_args, i, |s| args.pretty_syntax = Some(s))
             }
+            "--tabs" => Self::handle_numeric_flag(raw_args, i, |n| args.tabs = Some(n), "tabs"),
+            "--wrap" => Self::handle_wrap_flag(args, raw_args, i),
+            "--decorations" => Self::handle_decorations_flag(args, raw_args, i),
+            "--color" => Self::handle_color_flag(args, raw_args, i),
+            "--paging" => Self::handle_paging_flag(args, raw_args, i),
+            "--theme" => Self::handle_string_flag(raw_args, i, |s| args.theme = Some(s)),
+            "--list-themes" => Self::handle_bool_flag(i, &mut args.list_themes),
+            "--list-syntaxes" => Self::handle_bool_flag(i, &mut args.list_syntaxes),
+            "--json-schema" => Self::handle_bool_flag(i, &mut args.json_schema),
+            "--tree" => Self::handle_bool_flag(i, &mut args.tree),
+            "--tree-info" => Self::handle_tree_info_flag(args, raw_args, i),
+            "--stats" => Self::handle_stats_flag(args, raw_args, i),
+            "--secrets" => Self::handle_secrets_flag(args, raw_args, i),
+            "--secrets-allowlist" => Self::handle_string_flag(raw_args, i, |s| {
+                args.secrets_allowlist = Some(PathBuf::from(s));
+            }),
+            "--rewrite" => Self::handle_string_flag(raw_args, i, |s| args.rewrite.push(s)),
+            "--strip-comments" => Self::handle_bool_flag(i, &mut args.strip_comments),
+            "--strip-doc-comments" => Self::handle_bool_flag(i, &mut args.strip_doc_comments),
+            "--minify-whitespace" => Self::handle_bool_flag(i, &mut args.minify_whitespace),
+            "--prompt" => Self::handle_string_flag(raw_args, i, |s| args.prompt = Some(s)),
+            "--prompt-file" => Self::handle_string_flag(raw_args, i, |s| {
+                args.prompt_file = Some(PathBuf::from(s));
+            }),
+            "--template" => Self::handle_string_flag(raw_args, i, |s| {
+                args.template = Some(PathBuf::from(s));
+            }),
+            "--budget" => Self::handle_string_flag(raw_args, i, |s| args.budget = Some(s)),
+            "--split" => Self::handle_string_flag(raw_args, i, |s| args.split = Some(s)),
+            "--split-dir" => Self::handle_string_flag(raw_args, i, |s| {
+                args.split_dir = Some(PathBuf::from(s));
+            }),
+            "-o" | "--output" => Self::handle_string_flag(raw_args, i, |s| {
+                args.output = Some(PathBuf::from(s));
+            }),
+            "--tee" => Self::handle_bool_flag(i, &mut args.tee),
+            "--output-dir" => Self::handle_string_flag(raw_args, i, |s| {
+                args.output_dir = Some(PathBuf::from(s));
+            }),
+            "--must" => Self::handle_string_flag(raw_args, i, |s| args.must.push(PathBuf::from(s))),
             #[cfg(feature = "clipboard")]
             "--clipboard-provider-for-test" => Self::handle_string_flag(raw_args, i, |s| {
                 args.clipboard_provider_for_test = Some(s);
@@ -165,13 +392,342 @@ impl Args {
             "ansi" => args.format = Some(OutputFormat::Ansi),
             "xml" => args.format = Some(OutputFormat::Xml),
             "json" => args.format = Some(OutputFormat::Json),
+            "jsonl" => args.format = Some(OutputFormat::Jsonl),
+            "yaml" => args.format = Some(OutputFormat::Yaml),
+            "toml" => args.format = Some(OutputFormat::Toml),
+            "html" => args.format = Some(OutputFormat::Html),
             "markdown" => args.format = Some(OutputFormat::Markdown),
             "ascii" => args.format = Some(OutputFormat::Ascii),
             "utf8" => args.format = Some(OutputFormat::Utf8),
             "pretty" => args.format = Some(OutputFormat::Pretty),
+            "template" => args.format = Some(OutputFormat::Template),
+            "documents" => args.format = Some(OutputFormat::Documents),
+            "messages" => args.format = Some(OutputFormat::Messages),
+            _ => {
+                return Err(format!(
+                    "Invalid format '{format_str}'. Valid formats are: ansi, xml, json, jsonl, yaml, toml, markdown, ascii, utf8, pretty, html, template, documents, messages"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_tree_info_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let info_str = &raw_args[*i];
+        match info_str.as_str() {
+            "none" => args.tree_info = Some(TreeInfo::None),
+            "size" => args.tree_info = Some(TreeInfo::Size),
+            "lines" => args.tree_info = Some(TreeInfo::Lines),
+            _ => {
+                return Err(format!(
+                    "Invalid tree info '{info_str}'. Valid values are: none, size, lines"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_stats_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        // The mode is optional: anything else after the flag is a path.
+        *i += 1;
+        let mode = match raw_args.get(*i).map(String::as_str) {
+            Some("stderr") => Some(StatsMode::Stderr),
+            Some("footer") => Some(StatsMode::Footer),
+            Some("json") => Some(StatsMode::Json),
+            _ => None,
+        };
+        if mode.is_some() {
+            *i += 1;
+        }
+        args.stats = Some(mode.unwrap_or(StatsMode::Stderr));
+        Ok(true)
+    }
+
+    fn handle_markdown_fence_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let fence_str = &raw_args[*i];
+        match fence_str.as_str() {
+            "backticks" => args.markdown_fence = Some(MarkdownFence::Backticks),
+            "tildes" => args.markdown_fence = Some(MarkdownFence::Tildes),
+            _ => {
+                return Err(format!(
+                    "Invalid markdown fence '{fence_str}'. Valid values are: backticks, tildes"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_wrap_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let wrap_str = &raw_args[*i];
+        match wrap_str.as_str() {
+            "wrap" => args.wrap = Some(LineWrap::Wrap),
+            "truncate" => args.wrap = Some(LineWrap::Truncate),
+            "never" => args.wrap = Some(LineWrap::Never),
+            _ => {
+                return Err(format!(
+                    "Invalid wrap mode '{wrap_str}'. Valid values are: wrap, truncate, never"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_decorations_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let style_str = &raw_args[*i];
+        match style_str.as_str() {
+            "full" => args.decorations = Some(Decorations::Full),
+            "header" => args.decorations = Some(Decorations::Header),
+            "plain" => args.decorations = Some(Decorations::Plain),
+            _ => {
+                return Err(format!(
+                    "Invalid decorations '{style_str}'. Valid values are: full, header, plain"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_color_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let color_str = &raw_args[*i];
+        match color_str.as_str() {
+            "auto" => args.color = Some(ColorMode::Auto),
+            "always" => args.color = Some(ColorMode::Always),
+            "never" => args.color = Some(ColorMode::Never),
             _ => {
                 return Err(format!(
-                    "Invalid format '{format_str}'. Valid formats are: ansi, xml, json, markdown, ascii, utf8, pretty"
+                    "Invalid color mode '{color_str}'. Valid values are: auto, always, never"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_paging_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let paging_str = &raw_args[*i];
+        match paging_str.as_str() {
+            "auto" => args.paging = Some(Paging::Auto),
+            "always" => args.paging = Some(Paging::Always),
+            "never" => args.paging = Some(Paging::Never),
+            _ => {
+                return Err(format!(
+                    "Invalid paging mode '{paging_str}'. Valid values are: auto, always, never"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_html_style_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let style_str = &raw_args[*i];
+        match style_str.as_str() {
+            "inline" => args.html_style = Some(HtmlStyle::Inline),
+            "classes" => args.html_style = Some(HtmlStyle::Classes),
+            _ => {
+                return Err(format!(
+                    "Invalid HTML style '{style_str}'. Valid values are: inline, classes"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_markdown_header_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let header_str = &raw_args[*i];
+        match header_str.as_str() {
+            "front-matter" => args.markdown_header = Some(MarkdownHeader::FrontMatter),
+            "heading" => args.markdown_header = Some(MarkdownHeader::Heading),
+            "bold" => args.markdown_header = Some(MarkdownHeader::Bold),
+            _ => {
+                return Err(format!(
+                    "Invalid markdown header '{header_str}'. Valid values are: front-matter, heading, bold"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_messages_api_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let api_str = &raw_args[*i];
+        match api_str.as_str() {
+            "openai" => args.messages_api = Some(MessagesApi::Openai),
+            "anthropic" => args.messages_api = Some(MessagesApi::Anthropic),
+            _ => {
+                return Err(format!(
+                    "Invalid messages API '{api_str}'. Valid values are: openai, anthropic"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_messages_content_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let layout_str = &raw_args[*i];
+        match layout_str.as_str() {
+            "files" => args.messages_content = Some(MessagesContent::Files),
+            "single" => args.messages_content = Some(MessagesContent::Single),
+            _ => {
+                return Err(format!(
+                    "Invalid messages content '{layout_str}'. Valid values are: files, single"
+                ));
+            }
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_meta_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        for field_str in raw_args[*i].split(',') {
+            let field = match field_str.trim() {
+                "size" => MetaField::Size,
+                "mtime" => MetaField::Mtime,
+                "lines" => MetaField::Lines,
+                "lang" => MetaField::Lang,
+                "sha256" => MetaField::Sha256,
+                "mode" => MetaField::Mode,
+                _ => {
+                    return Err(format!(
+                        "Invalid metadata field '{field_str}'. Valid values are: size, mtime, lines, lang, sha256, mode"
+                    ));
+                }
+            };
+            args.meta.push(field);
+        }
+        *i += 1;
+        Ok(true)
+    }
+
+    fn handle_secrets_flag(
+        args: &mut Self,
+        raw_args: &[String],
+        i: &mut usize,
+    ) -> Result<bool, String> {
+        let arg = &raw_args[*i];
+        if *i + 1 >= raw_args.len() {
+            return Err(format!("The '{arg}' flag requires a value"));
+        }
+        *i += 1;
+        let mode_str = &raw_args[*i];
+        match mode_str.as_str() {
+            "redact" => args.secrets = Some(SecretsMode::Redact),
+            "warn" => args.secrets = Some(SecretsMode::Warn),
+            "refuse" => args.secrets = Some(SecretsMode::Refuse),
+            "off" => args.secrets = Some(SecretsMode::Off),
+            _ => {
+                return Err(format!(
+                    "Invalid secrets mode '{mode_str}'. Valid modes are: redact, warn, refuse, off"
                 ));
             }
         }
@@ -208,6 +764,13 @@ impl Args {
         )
     }
 
+    #[allow(clippy::unnecessary_wraps)] // Same signature as the other flag handlers
+    const fn handle_bool_flag(i: &mut usize, flag: &mut bool) -> Result<bool, String> {
+        *flag = true;
+        *i += 1;
+        Ok(true)
+    }
+
     fn handle_string_flag<F>(raw_args: &[String], i: &mut usize, setter: F) -> Result<bool, String>
     where
         F: FnOnce(String),
@@ -230,8 +793,14 @@ pub enum OutputFormat {
     Ansi,
     /// XML format
     Xml,
-    /// JSON format
+    /// JSON document (see --json-schema)
     Json,
+    /// JSON Lines, one object per file, streamed as files are read
+    Jsonl,
+    /// YAML with the fields of the JSON document, code in literal blocks
+    Yaml,
+    /// TOML with the fields of the JSON document, code in multi-line strings
+    Toml,
     /// Markdown code blocks
     Markdown,
     /// Simple ASCII header
@@ -240,4 +809,166 @@ pub enum OutputFormat {
     Utf8,
     /// Pretty-printed with syntax highlighting
     Pretty,
+    /// Self-contained HTML page with an index and highlighted files
+    Html,
+    /// User-defined template (see --template)
+    Template,
+    /// `<documents>` XML for long-context prompts
+    Documents,
+    /// Chat API request body (see --messages-api)
+    Messages,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TreeInfo {
+    /// File and directory names only
+    #[default]
+    None,
+    /// Size in bytes
+    Size,
+    /// Number of lines
+    Lines,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StatsMode {
+    /// Human-readable summary on stderr
+    Stderr,
+    /// Summary appended to the output in the format's own style
+    Footer,
+    /// JSON object on stderr, for scripts
+    Json,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SecretsMode {
+    /// Replace secrets with [REDACTED:kind] markers and report them on stderr
+    Redact,
+    /// Leave secrets in place but report them on stderr, and refuse to --copy them
+    #[default]
+    Warn,
+    /// Exit with an error if any secret is found
+    Refuse,
+    /// Do not scan for secrets
+    Off,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MessagesApi {
+    /// OpenAI-compatible chat completions (`messages` with a system message)
+    #[default]
+    Openai,
+    /// Anthropic Messages API (top-level `system` and `max_tokens`)
+    Anthropic,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MessagesContent {
+    /// One content block per file
+    #[default]
+    Files,
+    /// A single text block holding the whole bundle
+    Single,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MetaField {
+    /// Size in bytes
+    Size,
+    /// Last modification time (RFC 3339, UTC)
+    Mtime,
+    /// Number of lines
+    Lines,
+    /// Language, as detected from the file name
+    Lang,
+    /// SHA-256 digest of the contents
+    Sha256,
+    /// Permission bits in octal (Unix only)
+    Mode,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MarkdownFence {
+    /// ``` fences
+    #[default]
+    Backticks,
+    /// ~~~ fences
+    Tildes,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MarkdownHeader {
+    /// `---` / `File: path` / `---`
+    #[default]
+    FrontMatter,
+    /// `### path` heading
+    Heading,
+    /// `**path**` on a line of its own
+    Bold,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HtmlStyle {
+    /// `style` attributes on every token
+    #[default]
+    Inline,
+    /// `class` attributes and the theme as a `<style>` sheet
+    Classes,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineWrap {
+    /// Continue long lines on the next row
+    #[default]
+    Wrap,
+    /// Cut long lines off with `…`
+    Truncate,
+    /// Widen the box to fit the longest line
+    Never,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Decorations {
+    /// A file header and grid borders around the contents
+    #[default]
+    Full,
+    /// A file header only
+    Header,
+    /// The highlighted contents alone
+    Plain,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorMode {
+    /// Colours when writing to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Always use colours
+    Always,
+    /// Never use colours
+    Never,
+}
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Paging {
+    /// Page output that is longer than the terminal
+    #[default]
+    Auto,
+    /// Always page output
+    Always,
+    /// Never page output
+    Never,
 }
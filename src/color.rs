@@ -0,0 +1,298 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! How many colours the terminal can show, and escape codes to match.
+
+use crate::cli::ColorMode;
+use serde::Deserialize;
+use std::fmt::Write as _;
+use std::str::FromStr;
+use syntect::highlighting::{Color, Style};
+
+/// The colours output may use.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorLevel {
+    /// Plain text, no escape codes.
+    #[default]
+    None,
+    /// The 16 standard ANSI colours.
+    Ansi16,
+    /// The xterm 256-colour palette.
+    Ansi256,
+    /// 24-bit RGB.
+    TrueColor,
+}
+
+/// The 16 ANSI colours as xterm shows them by default.
+const ANSI16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel values of the 6×6×6 colour cube at 16–231 of the 256 palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorLevel {
+    /// The level for `mode` from the environment. `to_terminal` tells
+    /// whether output goes to a terminal.
+    #[must_use]
+    pub fn detect(mode: ColorMode, to_terminal: bool) -> Self {
+        Self::detect_with(mode, to_terminal, |name| std::env::var(name).ok())
+    }
+
+    /// Like [`ColorLevel::detect`], reading variables with `var`.
+    ///
+    /// `--color never` and, with `auto`, output that is not a terminal, a
+    /// non-empty `NO_COLOR` or `TERM=dumb` turn colours off. Otherwise
+    /// `COLORTERM=truecolor` (or `24bit`) selects 24-bit colour, a `TERM`
+    /// mentioning `256color` the 256-colour palette, and anything else the
+    /// 16 standard colours.
+    pub fn detect_with(
+        mode: ColorMode,
+        to_terminal: bool,
+        var: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let term = var("TERM").unwrap_or_default();
+        let auto = mode == ColorMode::Auto;
+        if mode == ColorMode::Never
+            || (auto && !to_terminal)
+            || (auto && var("NO_COLOR").is_some_and(|v| !v.is_empty()))
+            || (auto && term == "dumb")
+        {
+            return Self::None;
+        }
+        if var("COLORTERM").is_some_and(|v| v == "truecolor" || v == "24bit") {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    /// Escape code that sets the foreground (or with `background`, the
+    /// background) to the nearest colour available at this level.
+    #[must_use]
+    pub fn escape(self, c: Color, background: bool) -> String {
+        let rgb = (c.r, c.g, c.b);
+        match self {
+            Self::None => String::new(),
+            Self::TrueColor => {
+                let layer = if background { 48 } else { 38 };
+                format!("\x1b[{layer};2;{};{};{}m", c.r, c.g, c.b)
+            }
+            Self::Ansi256 => {
+                let layer = if background { 48 } else { 38 };
+                format!("\x1b[{layer};5;{}m", nearest_256(rgb))
+            }
+            Self::Ansi16 => {
+                let idx = nearest(rgb, ANSI16.iter().copied());
+                let base = match (background, idx < 8) {
+                    (false, true) => 30,
+                    (false, false) => 90 - 8,
+                    (true, true) => 40,
+                    (true, false) => 100 - 8,
+                };
+                format!("\x1b[{}m", base + idx)
+            }
+        }
+    }
+
+    /// Highlighted `ranges` as escaped text, also painting the theme's
+    /// background with `background`. Colours are reset at the end so they
+    /// do not leak into what follows.
+    #[must_use]
+    pub fn escape_ranges(self, ranges: &[(Style, &str)], background: bool) -> String {
+        let mut out = String::new();
+        if self == Self::None {
+            for (_, text) in ranges {
+                out.push_str(text);
+            }
+            return out;
+        }
+        for (style, text) in ranges {
+            if background {
+                out.push_str(&self.escape(style.background, true));
+            }
+            let _ = write!(out, "{}{text}", self.escape(style.foreground, false));
+        }
+        out.push_str("\x1b[0m");
+        out
+    }
+}
+
+/// A text style from the config: space-separated attributes (`bold`, `dim`,
+/// `italic`, `underline`) and a colour, either one of the 16 ANSI names
+/// (`blue`, `bright-red`) or `#rrggbb`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TextStyle {
+    pub bold: bool,
+    pub dim: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub color: Option<StyleColor>,
+}
+
+/// The colour of a [`TextStyle`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StyleColor {
+    /// One of the 16 ANSI colours, as the terminal shows it.
+    Named(u8),
+    /// An exact colour, matched as closely as the terminal allows.
+    Rgb(u8, u8, u8),
+}
+
+/// Names of the first 8 ANSI colours; `bright-` picks the other 8.
+const NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+impl TextStyle {
+    /// Escape code that turns the style on, or nothing at
+    /// [`ColorLevel::None`].
+    #[must_use]
+    pub fn escape(self, level: ColorLevel) -> String {
+        if level == ColorLevel::None {
+            return String::new();
+        }
+        let mut codes = Vec::new();
+        for (on, code) in [
+            (self.bold, "1"),
+            (self.dim, "2"),
+            (self.italic, "3"),
+            (self.underline, "4"),
+        ] {
+            if on {
+                codes.push(code.to_owned());
+            }
+        }
+        let mut out = if codes.is_empty() {
+            String::new()
+        } else {
+            format!("\x1b[{}m", codes.join(";"))
+        };
+        match self.color {
+            Some(StyleColor::Named(idx)) if idx < 8 => {
+                let _ = write!(out, "\x1b[{}m", 30 + idx);
+            }
+            Some(StyleColor::Named(idx)) => {
+                let _ = write!(out, "\x1b[{}m", 90 + idx - 8);
+            }
+            Some(StyleColor::Rgb(r, g, b)) => {
+                out.push_str(&level.escape(Color { r, g, b, a: 0xff }, false));
+            }
+            None => {}
+        }
+        out
+    }
+}
+
+impl FromStr for TextStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut style = Self::default();
+        for word in s.split_whitespace() {
+            match word {
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "italic" => style.italic = true,
+                "underline" => style.underline = true,
+                "plain" | "none" => {}
+                _ => {
+                    if style.color.is_some() {
+                        return Err(format!("style '{s}' has more than one colour"));
+                    }
+                    style.color = Some(parse_color(word).ok_or_else(|| {
+                        format!("unknown colour or attribute '{word}' in style '{s}'")
+                    })?);
+                }
+            }
+        }
+        Ok(style)
+    }
+}
+
+impl TryFrom<String> for TextStyle {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A colour name such as `bright-red`, or `#rrggbb`.
+fn parse_color(word: &str) -> Option<StyleColor> {
+    if let Some(hex) = word.strip_prefix('#') {
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+        };
+        return (hex.len() == 6)
+            .then(|| Some(StyleColor::Rgb(channel(0)?, channel(2)?, channel(4)?)))
+            .flatten();
+    }
+    let (bright, name) = word
+        .strip_prefix("bright-")
+        .map_or((false, word), |name| (true, name));
+    let idx = NAMES.iter().position(|&n| n == name)?;
+    let idx = u8::try_from(idx).ok()?;
+    Some(StyleColor::Named(if bright { idx + 8 } else { idx }))
+}
+
+/// Squared distance between two colours.
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Index of the colour in `palette` closest to `rgb`.
+fn nearest(rgb: (u8, u8, u8), palette: impl Iterator<Item = (u8, u8, u8)>) -> usize {
+    palette
+        .enumerate()
+        .min_by_key(|&(_, c)| distance(rgb, c))
+        .map_or(0, |(idx, _)| idx)
+}
+
+/// The closest entry of the colour cube or the grey ramp of the 256 palette.
+fn nearest_256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |v: u8| nearest((v, v, v), CUBE.iter().map(|&c| (c, c, c)));
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (CUBE[r], CUBE[g], CUBE[b]);
+    // Greys 232–255 run from 8 to 238 in steps of 10.
+    let grey = nearest(rgb, (0..24u8).map(|i| (8 + 10 * i, 8 + 10 * i, 8 + 10 * i)));
+    let grey_value = 8 + 10 * u8::try_from(grey).unwrap_or(0);
+    if distance(rgb, (grey_value, grey_value, grey_value)) < distance(rgb, cube) {
+        232 + u8::try_from(grey).unwrap_or(0)
+    } else {
+        16 + u8::try_from(36 * r + 6 * g + b).unwrap_or(0)
+    }
+}
@@ -14,64 +14,137 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
+use super::layout::{Layout, Paint};
+use super::pretty::syntax_for;
+use super::{FileMeta, Formatter};
+use crate::cli::LineWrap;
+use crate::color::{ColorLevel, StyleColor, TextStyle};
+use crate::highlight::{SYNTAX_SET, theme};
+use crate::stats::Stats;
+use crate::tree::Tree;
+use serde::Deserialize;
 use std::io::{self, Write};
 use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Style;
+use syntect::util::LinesWithEndings;
 
+/// Boxes like `utf8`, drawn in colour on terminals that have it.
 pub struct Ansi {
     pub width: usize,
     pub line_numbers: bool,
+    /// Columns per tab stop; 0 leaves tabs alone.
+    pub tabs: usize,
+    /// What to do with lines wider than the box.
+    pub wrap: LineWrap,
+    /// Colours to draw with; a plain box with [`ColorLevel::None`].
+    pub color: ColorLevel,
+    /// Styles of the box, from the `[ansi_style]` config table.
+    pub style: AnsiStyle,
+    /// Highlight the contents with `theme`.
+    pub highlight: bool,
+    /// Language to highlight as, as in the 'pretty' format.
+    pub syntax_override: Option<String>,
+    /// Name of the theme (see `--list-themes`); `None` for the default.
+    pub theme: Option<String>,
+}
+
+/// Styles of the parts of an `ansi` box, each a [`TextStyle`] such as
+/// `"bold blue"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AnsiStyle {
+    pub border: TextStyle,
+    pub header: TextStyle,
+    pub gutter: TextStyle,
+}
+
+impl Default for AnsiStyle {
+    fn default() -> Self {
+        Self {
+            border: TextStyle {
+                color: Some(StyleColor::Named(4)), // blue
+                ..TextStyle::default()
+            },
+            header: TextStyle {
+                bold: true,
+                ..TextStyle::default()
+            },
+            gutter: TextStyle {
+                dim: true,
+                ..TextStyle::default()
+            },
+        }
+    }
 }
 
 impl Formatter for Ansi {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
-        // ---------- collect body lines & determine interior width ----------
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
         let digits = if self.line_numbers {
-            content.lines().count().to_string().len()
+            meta.gutter_width(content.lines().count())
         } else {
             0
         };
+        let layout = self.layout();
+        let paint = self.paint();
+        let header = format!(" File: {}", meta.label(path));
+        let rows = if paint.text == ColorLevel::None {
+            let lines = content.lines().map(|line| vec![(Style::default(), line)]);
+            layout.painted_rows(lines, meta, digits, &paint)
+        } else {
+            let syntax = syntax_for(path, content, self.syntax_override.as_deref());
+            let mut h = HighlightLines::new(syntax, theme(self.theme.as_deref()));
+            let lines = LinesWithEndings::from(content).map(|line| {
+                h.highlight_line(line, &SYNTAX_SET)
+                    .unwrap_or_else(|_| vec![(Style::default(), line)]) // Fallback to plain line on error
+                    .into_iter()
+                    .map(|(style, text)| (style, text.trim_end_matches(['\n', '\r'])))
+                    .collect()
+            });
+            layout.painted_rows(lines, meta, digits, &paint)
+        };
+        layout.draw_painted(&header, &rows, &paint, w)
+    }
 
-        let mut body = Vec::new();
-        let mut interior = 0_usize;
-
-        for (idx, line) in content.lines().enumerate() {
-            let base = if self.line_numbers {
-                format!("{:>w$} │ {}", idx + 1, line, w = digits)
-            } else {
-                line.to_owned()
-            };
-            let rendered = base; // no extra leading space
-            interior = interior.max(rendered.len());
-            body.push(rendered);
-        }
-
-        let header = format!(" File: {}", path.display());
-        interior = interior.max(header.len());
-        interior = interior.max(self.width); // honour minimum width
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        let layout = self.layout();
+        let rows = layout.plain_rows(&tree.lines(true));
+        layout.draw_painted(" Tree", &rows, &self.paint(), w)
+    }
 
-        let hr = "─".repeat(interior);
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        let layout = self.layout();
+        let rows = layout.plain_rows(&stats.lines());
+        layout.draw_painted(" Stats", &rows, &self.paint(), w)
+    }
+}
 
-        // ---------------------------- print -------------------------------
-        writeln!(w, "┌{hr}┐")?;
-        writeln!(w, "│{}│", pad(&header, interior))?;
-        writeln!(w, "├{hr}┤")?;
-        for line in body {
-            writeln!(w, "│{}│", pad(&line, interior))?;
+impl Ansi {
+    const fn layout(&self) -> Layout {
+        Layout {
+            width: self.width,
+            tabs: self.tabs,
+            wrap: self.wrap,
         }
-        writeln!(w, "└{hr}┘")?;
-        Ok(())
     }
-}
 
-// helper (private to this file)
-fn pad(s: &str, width: usize) -> String {
-    if s.len() < width {
-        let mut out = String::with_capacity(width);
-        out.push_str(s);
-        out.extend(std::iter::repeat_n(' ', width - s.len()));
-        out
-    } else {
-        s.to_owned()
+    /// Escape codes of the box at `self.color`.
+    fn paint(&self) -> Paint {
+        Paint {
+            border: self.style.border.escape(self.color),
+            header: self.style.header.escape(self.color),
+            gutter: self.style.gutter.escape(self.color),
+            text: if self.highlight {
+                self.color
+            } else {
+                ColorLevel::None
+            },
+        }
     }
 }
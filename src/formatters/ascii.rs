@@ -14,7 +14,9 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
+use super::{FileMeta, Formatter};
+use crate::stats::Stats;
+use crate::tree::Tree;
 use std::io::{self, Write};
 use std::path::Path;
 
@@ -24,22 +26,44 @@ pub struct Ascii {
 }
 
 impl Formatter for Ascii {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
-        writeln!(w, "=== {} ===", path.display())?;
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        writeln!(w, "=== {} ===", meta.label(path))?;
         let total = content.lines().count();
         let width = if self.line_numbers {
-            total.to_string().len()
+            meta.gutter_width(total)
         } else {
             0
         };
         for (idx, line) in content.lines().enumerate() {
             if self.line_numbers {
                 //  number | content   (ASCII separator)
-                writeln!(w, "{:>w$} | {}", idx + 1, line, w = width)?;
+                writeln!(w, "{:>w$} | {}", meta.line_number(idx), line, w = width)?;
             } else {
                 writeln!(w, "{line}")?;
             }
         }
         Ok(())
     }
+
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "=== tree ===")?;
+        for line in tree.lines(false) {
+            writeln!(w, "{line}")?;
+        }
+        Ok(())
+    }
+
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "=== stats ===")?;
+        for line in stats.lines() {
+            writeln!(w, "{line}")?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,104 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use super::xml::{Xml, esc};
+use super::{FileMeta, Formatter};
+use crate::language::language_name;
+use crate::stats::Stats;
+use crate::tree::Tree;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The `<documents>` layout recommended for long-context prompts to Claude.
+pub struct Documents {
+    pub line_numbers: bool,
+    /// Add `<language>`, `<lines>` and `<bytes>` children to each document.
+    pub metadata: bool,
+}
+
+impl Documents {
+    /// Tree, stats, notes and prompts are written as in the `xml` format.
+    const fn xml(&self) -> Xml {
+        Xml {
+            line_numbers: self.line_numbers,
+            cdata: false,
+        }
+    }
+}
+
+impl Formatter for Documents {
+    fn begin_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<documents>")
+    }
+
+    fn end_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "</documents>")
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        writeln!(w, "<document index=\"{}\">", meta.index.max(1))?;
+        writeln!(w, "<source>{}</source>", esc(&path.display().to_string()))?;
+        if self.metadata {
+            writeln!(
+                w,
+                "<language>{}</language>",
+                esc(language_name(path, content))
+            )?;
+            writeln!(w, "<lines>{}</lines>", content.lines().count())?;
+            writeln!(w, "<bytes>{}</bytes>", content.len())?;
+        }
+        writeln!(w, "<document_content>")?;
+        if self.line_numbers {
+            let width = meta.gutter_width(content.lines().count());
+            for (idx, line) in content.lines().enumerate() {
+                writeln!(w, "{:>width$} | {}", meta.line_number(idx), esc(line))?;
+            }
+        } else {
+            write!(w, "{}", esc(content))?;
+            if !content.is_empty() && !content.ends_with('\n') {
+                writeln!(w)?;
+            }
+        }
+        writeln!(w, "</document_content>")?;
+        writeln!(w, "</document>")
+    }
+
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        self.xml().write_tree(tree, w)
+    }
+
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        self.xml().write_stats(stats, w)
+    }
+
+    fn write_preamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.xml().write_preamble(text, w)
+    }
+
+    fn write_postamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.xml().write_postamble(text, w)
+    }
+
+    fn write_note(&self, note: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.xml().write_note(note, w)
+    }
+}
@@ -0,0 +1,225 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use super::pretty::syntax_for;
+use super::xml::esc;
+use super::{FileMeta, Formatter};
+use crate::cli::HtmlStyle;
+use crate::highlight::{SYNTAX_SET, theme};
+use crate::stats::Stats;
+use crate::tree::Tree;
+use std::io::{self, Write};
+use std::path::Path;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::Color;
+use syntect::html::{
+    ClassStyle, ClassedHTMLGenerator, IncludeBackground, css_for_theme_with_class_style,
+    styled_line_to_highlighted_html,
+};
+use syntect::util::LinesWithEndings;
+
+/// A self-contained HTML page: an index linking to one highlighted section
+/// per file.
+pub struct Html {
+    pub line_numbers: bool,
+    pub style: HtmlStyle,
+    /// Language to highlight as, as in the 'pretty' format.
+    pub syntax_override: Option<String>,
+    /// Name of the theme, as for `pretty`.
+    pub theme: Option<String>,
+}
+
+/// Prefix of the classes used with `--html-style classes`, so the theme's
+/// stylesheet cannot clash with the page's own.
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+const PAGE_CSS: &str = "\
+body { font-family: system-ui, sans-serif; color: #222; max-width: 72rem; margin: 2rem auto; padding: 0 1rem; }
+h2 { font-size: 1rem; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; margin: 2rem 0 0.5rem; }
+h2 a, .index a { color: inherit; }
+pre { margin: 0; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 0.85rem; line-height: 1.4; }
+.code { overflow-x: auto; border-radius: 6px; }
+.code > pre, .code td { padding: 0.5rem 0.75rem; vertical-align: top; }
+.code table { border-collapse: collapse; }
+.code .ln { text-align: right; opacity: 0.5; user-select: none; border-right: 1px solid; }
+.prompt { white-space: pre-wrap; }
+.note { font-style: italic; color: #666; }
+";
+
+/// Anchor of the file at 1-based position `index`.
+fn anchor(index: usize) -> String {
+    format!("file-{}", index.max(1))
+}
+
+fn hex(c: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", c.r, c.g, c.b)
+}
+
+impl Html {
+    /// Attributes of the block around a file's code, carrying the theme's
+    /// background and foreground.
+    fn code_attrs(&self) -> String {
+        if self.style == HtmlStyle::Classes {
+            return "class=\"code hl-code\"".to_owned();
+        }
+        let settings = &theme(self.theme.as_deref()).settings;
+        let colors: Vec<String> = [
+            settings
+                .background
+                .map(|c| format!("background-color:{}", hex(c))),
+            settings.foreground.map(|c| format!("color:{}", hex(c))),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        format!("class=\"code\" style=\"{}\"", colors.join(";"))
+    }
+
+    /// `content` as highlighted HTML, or just escaped if highlighting fails.
+    fn highlight(&self, path: &Path, content: &str) -> String {
+        let syntax = syntax_for(path, content, self.syntax_override.as_deref());
+        let highlighted = if self.style == HtmlStyle::Classes {
+            let mut generator =
+                ClassedHTMLGenerator::new_with_class_style(syntax, &SYNTAX_SET, CLASS_STYLE);
+            LinesWithEndings::from(content)
+                .try_for_each(|line| generator.parse_html_for_line_which_includes_newline(line))
+                .ok()
+                .map(|()| generator.finalize())
+        } else {
+            let mut h = HighlightLines::new(syntax, theme(self.theme.as_deref()));
+            LinesWithEndings::from(content)
+                .map(|line| {
+                    let ranges = h.highlight_line(line, &SYNTAX_SET).ok()?;
+                    styled_line_to_highlighted_html(&ranges, IncludeBackground::No).ok()
+                })
+                .collect()
+        };
+        highlighted.unwrap_or_else(|| esc(content))
+    }
+}
+
+impl Formatter for Html {
+    fn begin_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<!DOCTYPE html>")?;
+        writeln!(w, "<html lang=\"en\">")?;
+        writeln!(w, "<head>")?;
+        writeln!(w, "<meta charset=\"utf-8\" />")?;
+        writeln!(
+            w,
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\" />"
+        )?;
+        writeln!(w, "<title>rucat</title>")?;
+        writeln!(w, "<style>")?;
+        write!(w, "{PAGE_CSS}")?;
+        if self.style == HtmlStyle::Classes {
+            let css = css_for_theme_with_class_style(theme(self.theme.as_deref()), CLASS_STYLE)
+                .map_err(io::Error::other)?;
+            write!(w, "{css}")?;
+        }
+        writeln!(w, "</style>")?;
+        writeln!(w, "</head>")?;
+        writeln!(w, "<body>")
+    }
+
+    fn end_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "</body>")?;
+        writeln!(w, "</html>")
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let id = anchor(meta.index);
+        writeln!(w, "<section id=\"{id}\">")?;
+        writeln!(
+            w,
+            "<h2><a href=\"#{id}\">{}</a></h2>",
+            esc(&meta.label(path))
+        )?;
+        // A newline right after `<pre>` is dropped by the parser, so one is
+        // always written to keep a leading blank line in the file.
+        let code = self.highlight(path, content);
+        if self.line_numbers {
+            let count = content.lines().count();
+            let mut gutter: Vec<String> = (0..count)
+                .map(|idx| meta.line_number(idx).to_string())
+                .collect();
+            if content.ends_with('\n') {
+                gutter.push(String::new());
+            }
+            writeln!(
+                w,
+                "<div {}><table><tr><td class=\"ln\"><pre>\n{}</pre></td><td><pre>\n{code}</pre></td></tr></table></div>",
+                self.code_attrs(),
+                gutter.join("\n")
+            )?;
+        } else {
+            writeln!(w, "<div {}><pre>\n{code}</pre></div>", self.code_attrs())?;
+        }
+        writeln!(w, "</section>")
+    }
+
+    fn write_index(&self, files: &[(usize, &Path)], w: &mut dyn Write) -> io::Result<()> {
+        let Some(&(first, _)) = files.first() else {
+            return Ok(());
+        };
+        writeln!(w, "<nav class=\"index\">")?;
+        writeln!(w, "<h2>Files</h2>")?;
+        writeln!(w, "<ol start=\"{}\">", first.max(1))?;
+        for &(index, path) in files {
+            writeln!(
+                w,
+                "<li><a href=\"#{}\">{}</a></li>",
+                anchor(index),
+                esc(&path.display().to_string())
+            )?;
+        }
+        writeln!(w, "</ol>")?;
+        writeln!(w, "</nav>")
+    }
+
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "<pre class=\"tree\">{}</pre>",
+            esc(&tree.lines(true).join("\n"))
+        )
+    }
+
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "<pre class=\"stats\">{}</pre>",
+            esc(&stats.lines().join("\n"))
+        )
+    }
+
+    fn write_preamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<div class=\"prompt\">{}</div>", esc(text.trim_end()))
+    }
+
+    fn write_postamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.write_preamble(text, w)
+    }
+
+    fn write_note(&self, note: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<p class=\"note\">{}</p>", esc(note))
+    }
+}
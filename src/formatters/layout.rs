@@ -0,0 +1,305 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! Fitting lines into the boxes of the `ansi` and `utf8` formats, measured
+//! in terminal columns rather than bytes or characters.
+
+use super::FileMeta;
+use crate::cli::LineWrap;
+use crate::color::ColorLevel;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use syntect::highlighting::Style;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Gutter mark of the rows a long line is wrapped onto.
+const CONTINUATION: &str = "↪";
+
+/// How a box format lays out its lines.
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    /// Interior width of the box in columns, excluding the borders.
+    pub width: usize,
+    /// Columns per tab stop; 0 leaves tabs alone.
+    pub tabs: usize,
+    /// What to do with lines wider than the box.
+    pub wrap: LineWrap,
+}
+
+/// Escape codes a box is drawn with. The default draws a plain box.
+#[derive(Debug, Clone, Default)]
+pub struct Paint {
+    /// Style of the borders.
+    pub border: String,
+    /// Style of the header text.
+    pub header: String,
+    /// Style of the line numbers and the gutter's rule.
+    pub gutter: String,
+    /// Colours of highlighted text.
+    pub text: ColorLevel,
+}
+
+impl Paint {
+    /// `s` in `style`, or unchanged without a style.
+    fn apply(style: &str, s: &str) -> String {
+        if style.is_empty() {
+            s.to_owned()
+        } else {
+            format!("{style}{s}\x1b[0m")
+        }
+    }
+}
+
+/// A row of a box: its text, which may hold escape codes, and the columns
+/// it takes up.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub text: String,
+    pub width: usize,
+}
+
+impl Layout {
+    /// The rows of `content` in the box, with line numbers in a gutter of
+    /// `digits` columns when `digits` is not 0.
+    #[must_use]
+    pub fn rows(&self, content: &str, meta: &FileMeta, digits: usize) -> Vec<Row> {
+        let lines = content.lines().map(|line| vec![(Style::default(), line)]);
+        self.painted_rows(lines, meta, digits, &Paint::default())
+    }
+
+    /// Rows of highlighted `lines`, each given as styled pieces without a
+    /// line ending, with the gutter and text coloured by `paint`.
+    #[must_use]
+    pub fn painted_rows<'a>(
+        &self,
+        lines: impl Iterator<Item = Vec<(Style, &'a str)>>,
+        meta: &FileMeta,
+        digits: usize,
+        paint: &Paint,
+    ) -> Vec<Row> {
+        let gutter = if digits == 0 { 0 } else { digits + 3 };
+        let max = self.width.saturating_sub(gutter).max(1);
+        let mut rows = Vec::new();
+        for (idx, line) in lines.enumerate() {
+            for (n, piece) in cut(&line, self.tabs, max, self.wrap)
+                .into_iter()
+                .enumerate()
+            {
+                let width = gutter + piece.iter().map(|(_, s)| width(s)).sum::<usize>();
+                let ranges: Vec<(Style, &str)> = piece
+                    .iter()
+                    .map(|(style, s)| (*style, s.as_str()))
+                    .collect();
+                let text = paint.text.escape_ranges(&ranges, false);
+                let text = match (digits, n) {
+                    (0, _) => text,
+                    (_, 0) => {
+                        let number = format!("{:>digits$} │", meta.line_number(idx));
+                        format!("{} {text}", Paint::apply(&paint.gutter, &number))
+                    }
+                    _ => {
+                        let mark = format!("{CONTINUATION:>digits$} │");
+                        format!("{} {text}", Paint::apply(&paint.gutter, &mark))
+                    }
+                };
+                rows.push(Row { text, width });
+            }
+        }
+        rows
+    }
+
+    /// Rows of lines without a gutter, such as the tree or statistics.
+    #[must_use]
+    pub fn plain_rows(&self, lines: &[String]) -> Vec<Row> {
+        let max = self.width.max(1);
+        lines
+            .iter()
+            .flat_map(|line| {
+                cut(
+                    &[(Style::default(), line.as_str())],
+                    self.tabs,
+                    max,
+                    self.wrap,
+                )
+            })
+            .map(|piece| {
+                let text: String = piece.into_iter().map(|(_, s)| s).collect();
+                Row {
+                    width: width(&text),
+                    text,
+                }
+            })
+            .collect()
+    }
+
+    /// Draws `body` in a box under `header`. With [`LineWrap::Never`] the
+    /// box grows to fit its widest row; otherwise a long header is cut off.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    pub fn draw(&self, header: &str, body: &[Row], w: &mut dyn Write) -> io::Result<()> {
+        self.draw_painted(header, body, &Paint::default(), w)
+    }
+
+    /// Like [`Layout::draw`], with the borders and header in the styles of
+    /// `paint`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    pub fn draw_painted(
+        &self,
+        header: &str,
+        body: &[Row],
+        paint: &Paint,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let (interior, header) = if self.wrap == LineWrap::Never {
+            let widest = body.iter().map(|row| row.width).max().unwrap_or(0);
+            (self.width.max(widest).max(width(header)), header.to_owned())
+        } else {
+            (self.width, truncate(header, self.width))
+        };
+
+        let hr = "─".repeat(interior);
+        let side = Paint::apply(&paint.border, "│");
+        let header = Paint::apply(&paint.header, &pad(&header, interior));
+
+        writeln!(w, "{}", Paint::apply(&paint.border, &format!("┌{hr}┐")))?;
+        writeln!(w, "{side}{header}{side}")?;
+        writeln!(w, "{}", Paint::apply(&paint.border, &format!("├{hr}┤")))?;
+        for row in body {
+            let fill = " ".repeat(interior.saturating_sub(row.width));
+            writeln!(w, "{side}{}{fill}{side}", row.text)?;
+        }
+        writeln!(w, "{}", Paint::apply(&paint.border, &format!("└{hr}┘")))?;
+        Ok(())
+    }
+}
+
+/// Columns `s` takes up on a terminal.
+#[must_use]
+pub fn width(s: &str) -> usize {
+    s.width()
+}
+
+/// `line` with every tab replaced by spaces up to the next multiple of
+/// `tabs` columns.
+#[must_use]
+pub fn expand_tabs(line: &str, tabs: usize) -> Cow<'_, str> {
+    expand_tabs_from(line, tabs, &mut 0)
+}
+
+/// Like [`expand_tabs`] for text starting at `column`, which is moved past
+/// the text.
+fn expand_tabs_from<'a>(text: &'a str, tabs: usize, column: &mut usize) -> Cow<'a, str> {
+    if tabs == 0 || !text.contains('\t') {
+        *column += width(text);
+        return text.into();
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c == '\t' {
+            let n = tabs - *column % tabs;
+            out.extend(std::iter::repeat_n(' ', n));
+            *column += n;
+        } else {
+            out.push(c);
+            *column += c.width().unwrap_or(0);
+        }
+    }
+    out.into()
+}
+
+/// The styled `line`, tabs expanded, as the pieces that fit in `max`
+/// columns under `wrap`.
+fn cut(
+    line: &[(Style, &str)],
+    tabs: usize,
+    max: usize,
+    wrap: LineWrap,
+) -> Vec<Vec<(Style, String)>> {
+    let mut column = 0;
+    let line: Vec<(Style, Cow<str>)> = line
+        .iter()
+        .map(|&(style, text)| (style, expand_tabs_from(text, tabs, &mut column)))
+        .collect();
+    // Truncated lines need a column for the `…`.
+    let (max, ellipsis) = match wrap {
+        LineWrap::Never => (usize::MAX, false),
+        LineWrap::Truncate if column > max => (max.saturating_sub(1), true),
+        LineWrap::Truncate | LineWrap::Wrap => (max, false),
+    };
+
+    let mut pieces = Vec::new();
+    let mut piece: Vec<(Style, String)> = Vec::new();
+    let mut column = 0;
+    let mut last = Style::default();
+    'line: for (style, text) in &line {
+        last = *style;
+        for c in text.chars() {
+            let w = c.width().unwrap_or(0);
+            if column + w > max && (column > 0 || ellipsis) {
+                if wrap != LineWrap::Wrap {
+                    break 'line;
+                }
+                pieces.push(std::mem::take(&mut piece));
+                column = 0;
+            }
+            match piece.last_mut() {
+                Some((s, text)) if s == style => text.push(c),
+                _ => piece.push((*style, c.to_string())),
+            }
+            column += w;
+        }
+    }
+    if ellipsis {
+        piece.push((last, "…".to_owned()));
+    }
+    pieces.push(piece);
+    pieces
+}
+
+/// `s` cut into pieces of at most `max` columns. A character wider than
+/// `max` gets a piece of its own.
+#[must_use]
+pub fn split(s: &str, max: usize) -> Vec<String> {
+    cut(&[(Style::default(), s)], 0, max, LineWrap::Wrap)
+        .into_iter()
+        .map(|piece| piece.into_iter().map(|(_, s)| s).collect())
+        .collect()
+}
+
+/// `s` cut to at most `max` columns, ending in `…` if anything was cut.
+#[must_use]
+pub fn truncate(s: &str, max: usize) -> String {
+    cut(&[(Style::default(), s)], 0, max, LineWrap::Truncate)
+        .into_iter()
+        .flatten()
+        .map(|(_, s)| s)
+        .collect()
+}
+
+/// `s` padded with spaces to `width` columns.
+#[must_use]
+pub fn pad(s: &str, width: usize) -> String {
+    let len = s.width();
+    let mut out = String::with_capacity(s.len() + width.saturating_sub(len));
+    out.push_str(s);
+    out.extend(std::iter::repeat_n(' ', width.saturating_sub(len)));
+    out
+}
@@ -14,33 +14,113 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
+use super::{FileMeta, Formatter};
+use crate::cli::{MarkdownFence, MarkdownHeader};
+use crate::language::Language;
+use crate::stats::Stats;
+use crate::tree::Tree;
 use std::io::{self, Write};
 use std::path::Path;
 
 pub struct Markdown {
     pub line_numbers: bool,
+    pub fence: MarkdownFence,
+    pub header: MarkdownHeader,
+}
+
+impl Markdown {
+    /// A fence of `self.fence` characters, at least three and longer than
+    /// any run of them in `text`, so the text cannot close it early.
+    fn fence_for<'a>(&self, text: impl IntoIterator<Item = &'a str>) -> String {
+        let c = match self.fence {
+            MarkdownFence::Backticks => '`',
+            MarkdownFence::Tildes => '~',
+        };
+        let longest = text
+            .into_iter()
+            .flat_map(|line| line.split(|ch| ch != c))
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        c.to_string().repeat(longest.max(2) + 1)
+    }
 }
 
 impl Formatter for Markdown {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
-        let extension = path.extension().and_then(|s| s.to_str()).unwrap_or("");
-        writeln!(w, "---\nFile: {}\n---", path.display())?;
-        writeln!(w, "```{extension}")?;
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let tag = Language::detect(path, content, None).map_or_else(
+            || path.extension().and_then(|s| s.to_str()).unwrap_or(""),
+            |l| l.id,
+        );
+        match self.header {
+            MarkdownHeader::FrontMatter => writeln!(w, "---\nFile: {}\n---", meta.label(path))?,
+            MarkdownHeader::Heading => writeln!(w, "### {}", meta.label(path))?,
+            MarkdownHeader::Bold => writeln!(w, "**{}**", meta.label(path))?,
+        }
+        let fence = self.fence_for([content]);
+        writeln!(w, "{fence}{tag}")?;
         let total = content.lines().count();
         let digits = if self.line_numbers {
-            total.to_string().len()
+            meta.gutter_width(total)
         } else {
             0
         };
         for (idx, line) in content.lines().enumerate() {
             if self.line_numbers {
-                writeln!(w, "{:>w$} {}", idx + 1, line, w = digits)?;
+                writeln!(w, "{:>w$} {}", meta.line_number(idx), line, w = digits)?;
             } else {
                 writeln!(w, "{line}")?;
             }
         }
-        writeln!(w, "```")?;
+        writeln!(w, "{fence}")?;
         Ok(())
     }
+
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        let lines = tree.lines(true);
+        let fence = self.fence_for(lines.iter().map(String::as_str));
+        writeln!(w, "{fence}")?;
+        for line in lines {
+            writeln!(w, "{line}")?;
+        }
+        writeln!(w, "{fence}")
+    }
+
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "\n---\n")?;
+        writeln!(
+            w,
+            "**Summary:** {} files ({} skipped), {} lines, {} bytes, ~{} tokens",
+            stats.files, stats.skipped, stats.lines, stats.bytes, stats.tokens
+        )?;
+        if !stats.languages.is_empty() {
+            writeln!(w, "\n| Language | Files | Lines | Bytes |")?;
+            writeln!(w, "| --- | ---: | ---: | ---: |")?;
+            for l in &stats.languages {
+                writeln!(
+                    w,
+                    "| {} | {} | {} | {} |",
+                    l.language, l.files, l.lines, l.bytes
+                )?;
+            }
+        }
+        if !stats.largest.is_empty() {
+            writeln!(w, "\n| Largest files | Lines | Bytes |")?;
+            writeln!(w, "| --- | ---: | ---: |")?;
+            for f in &stats.largest {
+                writeln!(w, "| `{}` | {} | {} |", f.path, f.lines, f.bytes)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn write_note(&self, note: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "> **rucat:** {note}")
+    }
 }
@@ -14,21 +14,166 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::meta::Metadata;
+use crate::stats::Stats;
+use crate::tree::Tree;
 use std::io::{self, Write};
 use std::path::Path;
 
+/// Extra information about a file that formatters may show alongside it.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileMeta<'a> {
+    /// Path of the file as given: before `--strip`, but after `--rewrite`
+    /// so rewrite rules can scrub it.
+    pub source: Option<&'a Path>,
+    /// 1-based position of the file in the output (0 when not known).
+    pub index: usize,
+    /// Original 1-based line number of each line of the content, when lines
+    /// were removed before formatting (e.g. by `--strip-comments`).
+    pub line_numbers: Option<&'a [usize]>,
+    /// Fields requested with `--meta`.
+    pub metadata: Option<&'a Metadata>,
+}
+
+impl FileMeta<'_> {
+    /// Line number to show for the 0-based line `idx` of the content.
+    #[must_use]
+    pub fn line_number(&self, idx: usize) -> usize {
+        self.line_numbers
+            .and_then(|n| n.get(idx).copied())
+            .unwrap_or(idx + 1)
+    }
+
+    /// Digits needed for the line numbers of content with `count` lines.
+    #[must_use]
+    pub fn gutter_width(&self, count: usize) -> usize {
+        let last = count.checked_sub(1).map_or(0, |idx| self.line_number(idx));
+        last.max(count).to_string().len()
+    }
+
+    /// `path` followed by the `--meta` summary, e.g. `src/main.rs (319 lines)`.
+    #[must_use]
+    pub fn label(&self, path: &Path) -> String {
+        self.metadata
+            .map_or_else(|| path.display().to_string(), |m| m.label(path))
+    }
+}
+
 pub trait Formatter {
+    /// Writes whatever opens the document, before anything else.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn begin_document(&self, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes whatever closes the document, after everything else.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn end_document(&self, _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
     /// Writes the content to the given writer, applying formatting.
     ///
     /// # Errors
     ///
     /// Will return `Err` if it fails to write to the given writer.
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()>;
+    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.write_file(path, content, &FileMeta::default(), w)
+    }
+
+    /// Like [`Formatter::write`], with extra information about the file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()>;
+
+    /// Writes a table of contents of the files that follow, each given as
+    /// its 1-based position in the output and its path. Only formats that
+    /// can link to the files write one.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_index(&self, _files: &[(usize, &Path)], _w: &mut dyn Write) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Writes the `--tree` overview that precedes the file contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        for line in tree.lines(true) {
+            writeln!(w, "{line}")?;
+        }
+        writeln!(w)
+    }
+
+    /// Writes the `--stats footer` summary that follows the file contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w)?;
+        for line in stats.lines() {
+            writeln!(w, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the `--prompt` text that precedes the bundle.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_preamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "{}", text.trim_end())?;
+        writeln!(w)
+    }
+
+    /// Writes the `--prompt` text that follows the bundle.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_postamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w)?;
+        writeln!(w, "{}", text.trim_end())
+    }
+
+    /// Writes a note from rucat itself (e.g. files left out by `--budget`),
+    /// set apart from the file contents.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if it fails to write to the given writer.
+    fn write_note(&self, note: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "[rucat] {note}")
+    }
 }
 
 pub mod ansi;
 pub mod ascii; // simple “===” header
+pub mod documents;
+pub mod html;
+pub mod layout; // box layout shared by ansi and utf8
 pub mod markdown;
 pub mod pretty;
+pub mod template;
 pub mod utf8; // fancy UTF-8 borders
 pub mod xml;
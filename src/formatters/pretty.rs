@@ -14,93 +14,176 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
-use regex::Regex;
+use super::{FileMeta, Formatter};
+use crate::cli::Decorations;
+use crate::color::ColorLevel;
+use crate::highlight::{SYNTAX_SET, theme};
+use crate::language::Language;
 use std::io::{self, Write};
 use std::path::Path;
-use std::sync::LazyLock;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::ThemeSet;
-use syntect::parsing::SyntaxSet;
-use syntect::util::{LinesWithEndings, as_24_bit_terminal_escaped};
-
-static MODELINE_RE: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"(?:vim|vi|ex)\s*:[^:]*:(?:ft|filetype|syntax)=([a-zA-Z0-9_.-]+)").unwrap()
-});
-
-/// Tries to find a syntax declaration in a Vim modeline.
-fn find_syntax_from_modeline(content: &str) -> Option<String> {
-    let lines: Vec<&str> = content.lines().collect();
-    let line_count = lines.len();
-
-    // Check the first 5 lines.
-    for line in lines.iter().take(5) {
-        if let Some(caps) = MODELINE_RE.captures(line)
-            && let Some(syntax) = caps.get(1)
-        {
-            return Some(syntax.as_str().to_owned());
-        }
-    }
-
-    // Check the last 5 lines if the file is long enough.
-    if line_count > 5 {
-        for line in lines.iter().rev().take(5) {
-            if let Some(caps) = MODELINE_RE.captures(line)
-                && let Some(syntax) = caps.get(1)
-            {
-                return Some(syntax.as_str().to_owned());
-            }
-        }
-    }
-    None
-}
+use syntect::highlighting::{Color, Theme};
+use syntect::parsing::SyntaxReference;
+use syntect::util::LinesWithEndings;
 
 pub struct Pretty {
     pub line_numbers: bool,
     pub syntax_override: Option<String>,
+    /// Name of the theme (see `--list-themes`); `None` for the default.
+    pub theme: Option<String>,
+    /// Colours to highlight with; plain text with [`ColorLevel::None`].
+    pub color: ColorLevel,
+    /// What to draw around each file besides its contents.
+    pub decorations: Decorations,
 }
 
-static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
-static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+/// Width of the grid's horizontal rules.
+const RULE_WIDTH: usize = 80;
+
+/// Syntax to highlight `content` with: `override_` (a language name or
+/// syntect token) if given, then the detected language, then the first line.
+pub(crate) fn syntax_for(
+    path: &Path,
+    content: &str,
+    override_: Option<&str>,
+) -> &'static SyntaxReference {
+    override_
+        .and_then(|s| {
+            Language::find(s)
+                .and_then(Language::syntax)
+                .or_else(|| SYNTAX_SET.find_syntax_by_token(s))
+        })
+        .or_else(|| Language::detect(path, content, None).and_then(Language::syntax))
+        .or_else(|| SYNTAX_SET.find_syntax_by_first_line(content))
+        .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text())
+}
 
 impl Formatter for Pretty {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
-        let syntax =
-            // 1. Check for --pretty-syntax flag override.
-            self.syntax_override
-                .as_deref()
-                .and_then(|s| SYNTAX_SET.find_syntax_by_token(s))
-            // 2. Check for a Vim modeline in the content.
-            .or_else(|| {
-                find_syntax_from_modeline(content)
-                    .and_then(|s| SYNTAX_SET.find_syntax_by_token(&s))
-            })
-            // 3. Fall back to the file extension.
-            .or_else(|| {
-                SYNTAX_SET.find_syntax_by_extension(path.extension().and_then(|s| s.to_str()).unwrap_or(""))
-            })
-            // 4. Finally, use plain text.
-            .unwrap_or_else(|| SYNTAX_SET.find_syntax_plain_text());
-        let mut h = HighlightLines::new(syntax, &THEME_SET.themes["base16-ocean.dark"]);
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let syntax = syntax_for(path, content, self.syntax_override.as_deref());
+        let theme = theme(self.theme.as_deref());
+        // Without colours there is nothing to highlight.
+        let mut h = (self.color != ColorLevel::None).then(|| HighlightLines::new(syntax, theme));
+        let gutter = self.gutter_style(theme);
+        let digits = meta.gutter_width(content.lines().count());
+        // Width of the gutter column the grid's junctions line up with.
+        let column = self.line_numbers.then_some(digits + 1);
 
-        if self.line_numbers {
-            let digits = content.lines().count().to_string().len();
-            for (idx, line) in LinesWithEndings::from(content).enumerate() {
-                let escaped = h.highlight_line(line, &SYNTAX_SET).map_or_else(
-                    |_| line.to_string(), // Fallback to plain line on error
-                    |ranges| as_24_bit_terminal_escaped(&ranges[..], true),
-                );
-                write!(w, "{:>w$} │ {escaped}", idx + 1, w = digits)?;
+        if self.decorations != Decorations::Plain {
+            let label = meta.label(path);
+            let file = if self.color == ColorLevel::None {
+                format!("File: {label}")
+            } else {
+                format!("File: \x1b[1m{label}\x1b[0m")
+            };
+            let language = Language::detect(path, content, self.syntax_override.as_deref())
+                .map_or(syntax.name.as_str(), |l| l.name);
+            let details = format!("Size: {}, Language: {language}", size(content.len()));
+            let grid = self.decorations == Decorations::Full;
+            if grid {
+                self.rule(column, '┬', &gutter, w)?;
             }
-        } else {
-            for line in LinesWithEndings::from(content) {
-                let escaped = h.highlight_line(line, &SYNTAX_SET).map_or_else(
-                    |_| line.to_string(), // Fallback to plain line on error
-                    |ranges| as_24_bit_terminal_escaped(&ranges[..], true),
-                );
-                write!(w, "{escaped}")?;
+            for text in [file, details] {
+                if grid && let Some(column) = column {
+                    write!(w, "{gutter}{:column$}│{} ", "", self.reset())?;
+                }
+                writeln!(w, "{text}")?;
+            }
+            if grid {
+                self.rule(column, '┼', &gutter, w)?;
+            }
+        }
+
+        for (idx, line) in LinesWithEndings::from(content).enumerate() {
+            let escaped = h.as_mut().map_or_else(
+                || line.to_string(),
+                |h| {
+                    h.highlight_line(line, &SYNTAX_SET).map_or_else(
+                        |_| line.to_string(), // Fallback to plain line on error
+                        |ranges| self.color.escape_ranges(&ranges, true),
+                    )
+                },
+            );
+            if self.line_numbers {
+                let number = meta.line_number(idx);
+                write!(w, "{gutter}{number:>digits$} │{} ", self.reset())?;
+            }
+            write!(w, "{escaped}")?;
+        }
+
+        if self.decorations != Decorations::Plain {
+            // Keep the last line off the rule or the next header.
+            if !content.is_empty() && !content.ends_with('\n') {
+                writeln!(w)?;
+            }
+            if self.decorations == Decorations::Full {
+                self.rule(column, '┴', &gutter, w)?;
+            } else {
+                writeln!(w)?;
             }
         }
         Ok(())
     }
 }
+
+impl Pretty {
+    /// Escape code for line numbers and borders: the theme's gutter colour,
+    /// or grey.
+    fn gutter_style(&self, theme: &Theme) -> String {
+        let grey = Color {
+            r: 0x80,
+            g: 0x80,
+            b: 0x80,
+            a: 0xff,
+        };
+        self.color
+            .escape(theme.settings.gutter_foreground.unwrap_or(grey), false)
+    }
+
+    /// Escape code that ends [`Pretty::gutter_style`], if there is one.
+    const fn reset(&self) -> &'static str {
+        match self.color {
+            ColorLevel::None => "",
+            _ => "\x1b[0m",
+        }
+    }
+
+    /// A horizontal rule of the grid, with `junction` where it crosses the
+    /// gutter `column` characters in.
+    fn rule(
+        &self,
+        column: Option<usize>,
+        junction: char,
+        gutter: &str,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let line = column.map_or_else(
+            || "─".repeat(RULE_WIDTH),
+            |column| {
+                let rest = RULE_WIDTH.saturating_sub(column + 1);
+                format!("{}{junction}{}", "─".repeat(column), "─".repeat(rest))
+            },
+        );
+        writeln!(w, "{gutter}{line}{}", self.reset())
+    }
+}
+
+/// `bytes` in B, KiB or MiB, e.g. `1.5 KiB`.
+fn size(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    #[allow(clippy::cast_precision_loss)] // one decimal place is shown
+    let scaled = |unit: usize| bytes as f64 / unit as f64;
+    if bytes < KIB {
+        format!("{bytes} B")
+    } else if bytes < KIB * KIB {
+        format!("{:.1} KiB", scaled(KIB))
+    } else {
+        format!("{:.1} MiB", scaled(KIB * KIB))
+    }
+}
@@ -0,0 +1,209 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use super::{FileMeta, Formatter};
+use crate::language::language_name;
+use std::io::{self, Write};
+use std::path::Path;
+
+const OPEN_FILES: &str = "{{#files}}";
+const CLOSE_FILES: &str = "{{/files}}";
+
+/// A `{{variable}}` of the per-file section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Var {
+    Path,
+    StrippedPath,
+    Ext,
+    Lang,
+    Content,
+    LineCount,
+    NumberedContent,
+    Index,
+}
+
+impl Var {
+    const ALL: [(&'static str, Self); 8] = [
+        ("path", Self::Path),
+        ("stripped_path", Self::StrippedPath),
+        ("ext", Self::Ext),
+        ("lang", Self::Lang),
+        ("content", Self::Content),
+        ("line_count", Self::LineCount),
+        ("numbered_content", Self::NumberedContent),
+        ("index", Self::Index),
+    ];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    Var(Var),
+}
+
+/// A user-defined output format.
+///
+/// The text between `{{#files}}` and `{{/files}}` is repeated for every
+/// file; the text before and after it opens and closes the document. A
+/// template without a `{{#files}}` section is repeated for every file as a
+/// whole.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    header: String,
+    file: Vec<Part>,
+    footer: String,
+}
+
+impl Template {
+    /// Parse template text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message for an unknown variable, an unclosed
+    /// `{{`, a `{{#files}}` without `{{/files}}`, or a variable outside the
+    /// per-file section.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let Some(open) = text.find(OPEN_FILES) else {
+            return Ok(Self {
+                header: String::new(),
+                file: parse_parts(text)?,
+                footer: String::new(),
+            });
+        };
+        let body = skip_newline(&text[open + OPEN_FILES.len()..]);
+        let close = body
+            .find(CLOSE_FILES)
+            .ok_or_else(|| format!("template has {OPEN_FILES} without {CLOSE_FILES}"))?;
+        let header = &text[..open];
+        let footer = skip_newline(&body[close + CLOSE_FILES.len()..]);
+        for outside in [header, footer] {
+            if parse_parts(outside)?
+                .iter()
+                .any(|p| matches!(p, Part::Var(_)))
+            {
+                return Err(format!(
+                    "template variables can only be used between {OPEN_FILES} and {CLOSE_FILES}"
+                ));
+            }
+        }
+        Ok(Self {
+            header: header.to_owned(),
+            file: parse_parts(&body[..close])?,
+            footer: footer.to_owned(),
+        })
+    }
+}
+
+/// Drop the newline after a section tag on a line of its own.
+fn skip_newline(s: &str) -> &str {
+    s.strip_prefix("\r\n")
+        .or_else(|| s.strip_prefix('\n'))
+        .unwrap_or(s)
+}
+
+fn parse_parts(mut text: &str) -> Result<Vec<Part>, String> {
+    let mut parts = Vec::new();
+    while let Some(start) = text.find("{{") {
+        if start > 0 {
+            parts.push(Part::Text(text[..start].to_owned()));
+        }
+        let rest = &text[start + 2..];
+        let end = rest
+            .find("}}")
+            .ok_or_else(|| "unclosed '{{' in template".to_owned())?;
+        let name = rest[..end].trim();
+        let var = Var::ALL
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| *v)
+            .ok_or_else(|| {
+                let valid: Vec<&str> = Var::ALL.iter().map(|(n, _)| *n).collect();
+                format!(
+                    "Unknown template variable '{name}'. Valid variables are: {}",
+                    valid.join(", ")
+                )
+            })?;
+        parts.push(Part::Var(var));
+        text = &rest[end + 2..];
+    }
+    if !text.is_empty() {
+        parts.push(Part::Text(text.to_owned()));
+    }
+    Ok(parts)
+}
+
+impl Formatter for Template {
+    fn begin_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self.header.as_bytes())
+    }
+
+    fn end_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        w.write_all(self.footer.as_bytes())
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        for part in &self.file {
+            match part {
+                Part::Text(text) => w.write_all(text.as_bytes())?,
+                Part::Var(var) => write_var(*var, path, content, meta, w)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_var(
+    var: Var,
+    path: &Path,
+    content: &str,
+    meta: &FileMeta,
+    w: &mut dyn Write,
+) -> io::Result<()> {
+    match var {
+        Var::Path => write!(w, "{}", meta.source.unwrap_or(path).display()),
+        Var::StrippedPath => write!(w, "{}", path.display()),
+        Var::Ext => write!(
+            w,
+            "{}",
+            path.extension().unwrap_or_default().to_string_lossy()
+        ),
+        Var::Lang => write!(w, "{}", language_name(path, content)),
+        Var::Content => {
+            w.write_all(content.as_bytes())?;
+            if content.ends_with('\n') || content.is_empty() {
+                Ok(())
+            } else {
+                writeln!(w)
+            }
+        }
+        Var::LineCount => write!(w, "{}", content.lines().count()),
+        Var::NumberedContent => {
+            let total = content.lines().count();
+            let width = meta.gutter_width(total);
+            for (idx, line) in content.lines().enumerate() {
+                writeln!(w, "{:>width$} | {line}", meta.line_number(idx))?;
+            }
+            Ok(())
+        }
+        Var::Index => write!(w, "{}", meta.index.max(1)),
+    }
+}
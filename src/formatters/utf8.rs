@@ -14,7 +14,11 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
+use super::layout::Layout;
+use super::{FileMeta, Formatter};
+use crate::cli::LineWrap;
+use crate::stats::Stats;
+use crate::tree::Tree;
 use std::io::{self, Write};
 use std::path::Path;
 
@@ -22,54 +26,47 @@ use std::path::Path;
 pub struct Utf8 {
     pub width: usize,
     pub line_numbers: bool,
+    /// Columns per tab stop; 0 leaves tabs alone.
+    pub tabs: usize,
+    /// What to do with lines wider than the box.
+    pub wrap: LineWrap,
 }
 
 impl Formatter for Utf8 {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
         let digits = if self.line_numbers {
-            content.lines().count().to_string().len()
+            meta.gutter_width(content.lines().count())
         } else {
             0
         };
+        let layout = self.layout();
+        let header = format!(" File: {} ", meta.label(path));
+        layout.draw(&header, &layout.rows(content, meta, digits), w)
+    }
 
-        let mut body = Vec::new();
-        let mut interior = 0_usize;
-
-        for (idx, raw) in content.lines().enumerate() {
-            let base = if self.line_numbers {
-                format!("{:>w$} │ {}", idx + 1, raw, w = digits)
-            } else {
-                raw.to_owned()
-            };
-            let rendered = base;
-            interior = interior.max(rendered.len());
-            body.push(rendered);
-        }
-
-        let header = format!(" File: {} ", path.display());
-        interior = interior.max(header.len());
-        interior = interior.max(self.width);
-
-        let hr = "─".repeat(interior);
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        let layout = self.layout();
+        layout.draw(" Tree ", &layout.plain_rows(&tree.lines(true)), w)
+    }
 
-        writeln!(w, "┌{hr}┐")?;
-        writeln!(w, "│{}│", pad(&header, interior))?;
-        writeln!(w, "├{hr}┤")?;
-        for line in body {
-            writeln!(w, "│{}│", pad(&line, interior))?;
-        }
-        writeln!(w, "└{hr}┘")?;
-        Ok(())
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        let layout = self.layout();
+        layout.draw(" Stats ", &layout.plain_rows(&stats.lines()), w)
     }
 }
 
-fn pad(s: &str, width: usize) -> String {
-    if s.len() < width {
-        let mut out = String::with_capacity(width);
-        out.push_str(s);
-        out.extend(std::iter::repeat_n(' ', width - s.len()));
-        out
-    } else {
-        s.to_owned()
+impl Utf8 {
+    const fn layout(&self) -> Layout {
+        Layout {
+            width: self.width,
+            tabs: self.tabs,
+            wrap: self.wrap,
+        }
     }
 }
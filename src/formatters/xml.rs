@@ -14,38 +14,171 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use super::Formatter;
+use super::{FileMeta, Formatter};
+use crate::meta::Metadata;
+use crate::stats::Stats;
+use crate::tree::{Tree, TreeNode};
+use std::fmt::Write as _;
 use std::io::{self, Write};
 use std::path::Path;
 
 pub struct Xml {
     pub line_numbers: bool,
+    /// Wrap contents in CDATA sections instead of escaping them.
+    pub cdata: bool,
 }
 
-fn esc(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-        .replace('\'', "&apos;")
+/// Escape text for use in XML content or attribute values. Characters XML
+/// 1.0 does not allow at all, even as references (most C0 controls), become
+/// U+FFFD.
+pub(crate) fn esc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(allowed(c)),
+        }
+    }
+    out
+}
+
+/// Wrap text in a CDATA section, splitting any `]]>` in it across two
+/// sections so it cannot end the first one early. Characters XML 1.0 does
+/// not allow become U+FFFD, as in [`esc`].
+pub(crate) fn cdata(s: &str) -> String {
+    let text: String = s.chars().map(allowed).collect();
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+/// `c`, or U+FFFD if it is not a `Char` in XML 1.0.
+const fn allowed(c: char) -> char {
+    match c {
+        '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'.. => c,
+        _ => char::REPLACEMENT_CHARACTER,
+    }
+}
+
+impl Xml {
+    fn text(&self, s: &str) -> String {
+        if self.cdata { cdata(s) } else { esc(s) }
+    }
 }
 
 impl Formatter for Xml {
-    fn write(&self, path: &Path, content: &str, w: &mut dyn Write) -> io::Result<()> {
+    fn begin_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(w, "<files>")
+    }
+
+    fn end_document(&self, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "</files>")
+    }
+
+    fn write_file(
+        &self,
+        path: &Path,
+        content: &str,
+        meta: &FileMeta,
+        w: &mut dyn Write,
+    ) -> io::Result<()> {
+        let mut attrs = String::new();
+        for (name, value) in meta.metadata.map(Metadata::attributes).unwrap_or_default() {
+            let _ = write!(attrs, " {name}=\"{}\"", esc(&value));
+        }
+        let path = esc(&path.display().to_string());
         if self.line_numbers {
-            writeln!(w, "<file path=\"{}\">", path.display())?;
+            writeln!(w, "<file path=\"{path}\"{attrs}>")?;
             for (idx, line) in content.lines().enumerate() {
-                writeln!(w, "  <line no=\"{}\">{}</line>", idx + 1, esc(line))?;
+                writeln!(
+                    w,
+                    "  <line no=\"{}\">{}</line>",
+                    meta.line_number(idx),
+                    self.text(line)
+                )?;
             }
             writeln!(w, "</file>")?;
         } else {
             writeln!(
                 w,
-                "<file path=\"{}\">{}</file>",
-                path.display(),
-                esc(content)
+                "<file path=\"{path}\"{attrs}>{}</file>",
+                self.text(content)
             )?;
         }
         Ok(())
     }
+
+    fn write_tree(&self, tree: &Tree, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<tree>")?;
+        for node in &tree.roots {
+            write_node(node, 1, w)?;
+        }
+        writeln!(w, "</tree>")
+    }
+
+    fn write_stats(&self, stats: &Stats, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(
+            w,
+            "<stats files=\"{}\" skipped=\"{}\" lines=\"{}\" bytes=\"{}\" tokens=\"{}\">",
+            stats.files, stats.skipped, stats.lines, stats.bytes, stats.tokens
+        )?;
+        for l in &stats.languages {
+            writeln!(
+                w,
+                "  <language name=\"{}\" files=\"{}\" lines=\"{}\" bytes=\"{}\"/>",
+                esc(&l.language),
+                l.files,
+                l.lines,
+                l.bytes
+            )?;
+        }
+        for f in &stats.largest {
+            writeln!(
+                w,
+                "  <largest path=\"{}\" lines=\"{}\" bytes=\"{}\"/>",
+                esc(&f.path),
+                f.lines,
+                f.bytes
+            )?;
+        }
+        writeln!(w, "</stats>")
+    }
+
+    fn write_preamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<instructions>{}</instructions>", esc(text.trim_end()))
+    }
+
+    fn write_postamble(&self, text: &str, w: &mut dyn Write) -> io::Result<()> {
+        self.write_preamble(text, w)
+    }
+
+    fn write_note(&self, note: &str, w: &mut dyn Write) -> io::Result<()> {
+        writeln!(w, "<note>{}</note>", esc(note))
+    }
+}
+
+/// Directories become `<dir>` elements, files become empty `<file/>` elements.
+fn write_node(node: &TreeNode, depth: usize, w: &mut dyn Write) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    let size = node
+        .size
+        .map(|n| format!(" size=\"{n}\""))
+        .unwrap_or_default();
+    let lines = node
+        .lines
+        .map(|n| format!(" lines=\"{n}\""))
+        .unwrap_or_default();
+    let attrs = format!("name=\"{}\"{size}{lines}", esc(&node.name));
+    if node.is_dir() {
+        writeln!(w, "{indent}<dir {attrs}>")?;
+        for child in &node.children {
+            write_node(child, depth + 1, w)?;
+        }
+        writeln!(w, "{indent}</dir>")
+    } else {
+        writeln!(w, "{indent}<file {attrs}/>")
+    }
 }
@@ -0,0 +1,238 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! Syntaxes and themes for the highlighting formats.
+//!
+//! The sets bundled with syntect are extended with any `.sublime-syntax`
+//! files in `<config dir>/syntaxes` and `.tmTheme` files in
+//! `<config dir>/themes`. Building a syntax set is slow, so the extended sets
+//! are kept as precompiled dumps in the cache directory and rebuilt only when
+//! the extra files change.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, OnceLock};
+use std::time::UNIX_EPOCH;
+use syntect::dumps::{dump_to_file, from_dump_file};
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxDefinition, SyntaxSet};
+
+/// Theme for dark terminals, and for everything when nothing else is known.
+pub const DEFAULT_DARK_THEME: &str = "base16-ocean.dark";
+/// Theme for light terminals.
+pub const DEFAULT_LIGHT_THEME: &str = "InspiredGitHub";
+
+/// Where extra syntaxes and themes are read from and their dumps cached.
+#[derive(Debug, Default)]
+struct Dirs {
+    config: Option<PathBuf>,
+    cache: Option<PathBuf>,
+}
+
+static DIRS: OnceLock<Dirs> = OnceLock::new();
+
+/// Set where extra syntaxes and themes come from and where they are cached.
+///
+/// `config` holds the `syntaxes/` and `themes/` directories and `cache`
+/// receives their dumps. Must be called before anything is highlighted;
+/// without it only the bundled syntaxes and themes are available.
+pub fn set_dirs(config: Option<PathBuf>, cache: Option<PathBuf>) {
+    let _ = DIRS.set(Dirs { config, cache });
+}
+
+fn dirs() -> &'static Dirs {
+    DIRS.get_or_init(Dirs::default)
+}
+
+pub(crate) static SYNTAX_SET: LazyLock<SyntaxSet> = LazyLock::new(|| {
+    load(
+        "syntaxes",
+        "sublime-syntax",
+        SyntaxSet::load_defaults_newlines,
+        |defaults, files| {
+            let mut builder = defaults.into_builder();
+            for path in files {
+                let name = path.file_stem().and_then(|s| s.to_str());
+                match fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|text| {
+                        SyntaxDefinition::load_from_str(&text, true, name)
+                            .map_err(|e| e.to_string())
+                    }) {
+                    Ok(syntax) => builder.add(syntax),
+                    Err(e) => eprintln!("Skipping syntax {}: {e}", path.display()),
+                }
+            }
+            builder.build()
+        },
+    )
+});
+
+static THEME_SET: LazyLock<ThemeSet> = LazyLock::new(|| {
+    load(
+        "themes",
+        "tmTheme",
+        ThemeSet::load_defaults,
+        |mut set, files| {
+            for path in files {
+                let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                    continue;
+                };
+                match ThemeSet::get_theme(path) {
+                    Ok(theme) => {
+                        set.themes.insert(name.to_owned(), theme);
+                    }
+                    Err(e) => eprintln!("Skipping theme {}: {e}", path.display()),
+                }
+            }
+            set
+        },
+    )
+});
+
+/// The bundled set from `defaults`, extended by `extend` with the `*.ext`
+/// files in `<config dir>/kind`. The result is cached in
+/// `<cache dir>/kind.bin` together with a fingerprint of those files.
+fn load<T, D, E>(kind: &str, ext: &str, defaults: D, extend: E) -> T
+where
+    T: Serialize + DeserializeOwned,
+    D: FnOnce() -> T,
+    E: FnOnce(T, &[PathBuf]) -> T,
+{
+    let dirs = dirs();
+    let files = dirs
+        .config
+        .as_ref()
+        .map(|dir| extra_files(&dir.join(kind), ext))
+        .unwrap_or_default();
+    if files.is_empty() {
+        return defaults();
+    }
+    let fingerprint = fingerprint(&files);
+    let cache = dirs
+        .cache
+        .as_ref()
+        .map(|dir| dir.join(format!("{kind}.bin")));
+    if let Some(cache) = &cache
+        && let Ok((cached, set)) = from_dump_file::<(String, T), _>(cache)
+        && cached == fingerprint
+    {
+        return set;
+    }
+    let set = extend(defaults(), &files);
+    let Some(cache) = cache else {
+        return set;
+    };
+    let dumped = (fingerprint, set);
+    // The cache only saves time, so failing to write it is not an error.
+    if let Some(dir) = cache.parent() {
+        let _ = fs::create_dir_all(dir);
+    }
+    let _ = dump_to_file(&dumped, &cache);
+    dumped.1
+}
+
+/// The `*.ext` files in `dir`, sorted by name.
+fn extra_files(dir: &Path, ext: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == ext) && path.is_file())
+        .collect();
+    files.sort();
+    files
+}
+
+/// Identifies this version of rucat and the name, size and modification
+/// time of every file, so the cache is rebuilt when any of them changes.
+fn fingerprint(files: &[PathBuf]) -> String {
+    let mut fingerprint = env!("CARGO_PKG_VERSION").to_owned();
+    for path in files {
+        let stat = fs::metadata(path).ok();
+        let mtime = stat
+            .as_ref()
+            .and_then(|m| m.modified().ok())
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos());
+        let size = stat.map_or(0, |m| m.len());
+        let _ = write!(fingerprint, "\n{} {size} {mtime}", path.display());
+    }
+    fingerprint
+}
+
+/// The theme called `name`, or the default dark theme.
+pub(crate) fn theme(name: Option<&str>) -> &'static Theme {
+    name.and_then(|name| THEME_SET.themes.get(name))
+        .unwrap_or_else(|| &THEME_SET.themes[DEFAULT_DARK_THEME])
+}
+
+/// Names of the available themes, sorted.
+pub fn theme_names() -> Vec<&'static str> {
+    THEME_SET.themes.keys().map(String::as_str).collect()
+}
+
+/// The available syntaxes as `Name: ext, ext`, sorted by name.
+pub fn syntax_names() -> Vec<String> {
+    let mut names: Vec<String> = SYNTAX_SET
+        .syntaxes()
+        .iter()
+        .filter(|s| !s.hidden)
+        .map(|s| format!("{}: {}", s.name, s.file_extensions.join(", ")))
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+/// Pick the theme: `requested` unless it is missing or `auto`, in which case
+/// `light` or `dark` (or their defaults) depending on the terminal
+/// background.
+///
+/// # Errors
+///
+/// Returns an error if the chosen theme does not exist.
+pub fn resolve_theme(
+    requested: Option<&str>,
+    light: Option<&str>,
+    dark: Option<&str>,
+) -> anyhow::Result<String> {
+    let name = match requested {
+        Some(name) if name != "auto" => name,
+        _ if background_is_light() => light.unwrap_or(DEFAULT_LIGHT_THEME),
+        _ => dark.unwrap_or(DEFAULT_DARK_THEME),
+    };
+    if !THEME_SET.themes.contains_key(name) {
+        anyhow::bail!("unknown theme '{name}' (see --list-themes)");
+    }
+    Ok(name.to_owned())
+}
+
+/// Whether the terminal has a light background, going by `COLORFGBG`.
+///
+/// The variable (`fg;bg`) is set by rxvt, Konsole and others. A background
+/// of 7 (white) or a bright colour other than 8 (dark grey) counts as light;
+/// without the variable terminals are assumed to be dark.
+#[must_use]
+pub fn background_is_light() -> bool {
+    std::env::var("COLORFGBG")
+        .ok()
+        .and_then(|v| v.rsplit(';').next()?.parse::<u8>().ok())
+        .is_some_and(|bg| bg == 7 || (9..=15).contains(&bg))
+}
@@ -0,0 +1,86 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::meta::Metadata;
+use crate::stats::Stats;
+use crate::tree::Tree;
+use serde::Serialize;
+
+/// Version of the `--format json` document, bumped only when existing fields
+/// change meaning or are removed.
+pub const VERSION: u32 = 1;
+
+/// JSON Schema of the `json` document and the `jsonl` lines, as printed by
+/// `--json-schema`.
+pub const SCHEMA: &str = include_str!("../assets/schema/rucat.schema.json");
+
+/// A file in the output.
+#[derive(Debug, Serialize)]
+pub struct FileEntry {
+    pub path: String,
+    #[serde(flatten)]
+    pub meta: Metadata,
+    pub content: String,
+}
+
+/// A file that could not be read.
+#[derive(Debug, Clone, Serialize)]
+pub struct FileError {
+    pub path: String,
+    pub error: String,
+}
+
+/// The `--format json` document.
+#[derive(Debug, Serialize)]
+pub struct Document<'a> {
+    pub version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree: Option<&'a Tree>,
+    pub files: &'a [FileEntry],
+    pub errors: &'a [FileError],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<&'a Stats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_after: Option<&'a str>,
+}
+
+/// One line of `--format jsonl`, tagged with its `type`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Line<'a> {
+    Part { part: String },
+    Prompt { text: &'a str },
+    Tree { tree: &'a Tree },
+    File(&'a FileEntry),
+    Error(&'a FileError),
+    Stats(&'a Stats),
+    PromptAfter { text: &'a str },
+}
+
+impl Line<'_> {
+    /// The line as compact JSON, without the trailing newline.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the line cannot be serialised.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
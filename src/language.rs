@@ -0,0 +1,280 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::highlight::SYNTAX_SET;
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+use syntect::parsing::SyntaxReference;
+
+/// A programming or markup language, as detected by [`Language::detect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// Canonical lowercase identifier, as used in Markdown code fences
+    /// (`rust`, `python`, `bash`); empty for plain text, which has no tag.
+    pub id: &'static str,
+    /// Human-readable name (`Rust`, `Python`, `Shell`).
+    pub name: &'static str,
+}
+
+struct Definition {
+    id: &'static str,
+    name: &'static str,
+    extensions: &'static [&'static str],
+    /// Exact file names; `Name.*` also matches the name with any suffix
+    /// (`Dockerfile.dev`).
+    filenames: &'static [&'static str],
+    /// Program names in `#!` lines.
+    interpreters: &'static [&'static str],
+    /// Other names used by modelines and overrides.
+    aliases: &'static [&'static str],
+}
+
+macro_rules! lang {
+    ($id:literal, $name:literal, [$($ext:literal),*], [$($file:literal),*], [$($interp:literal),*], [$($alias:literal),*]) => {
+        Definition {
+            id: $id,
+            name: $name,
+            extensions: &[$($ext),*],
+            filenames: &[$($file),*],
+            interpreters: &[$($interp),*],
+            aliases: &[$($alias),*],
+        }
+    };
+}
+
+#[rustfmt::skip]
+static LANGUAGES: &[Definition] = &[
+    lang!("awk", "Awk", ["awk"], [], ["awk", "gawk", "mawk", "nawk"], []),
+    lang!("bash", "Shell", ["sh", "bash", "ksh", "ebuild"], [".bashrc", ".bash_profile", ".bash_login", ".bash_logout", ".profile", "PKGBUILD"], ["sh", "bash", "dash", "ksh", "ash"], ["shell", "shell-script", "shellscript", "sh"]),
+    lang!("batch", "Batch", ["bat", "cmd"], [], [], ["bat", "dosbatch"]),
+    lang!("c", "C", ["c", "h"], [], [], []),
+    lang!("clojure", "Clojure", ["clj", "cljs", "cljc", "edn"], [], ["clojure", "bb"], []),
+    lang!("cmake", "CMake", ["cmake"], ["CMakeLists.txt"], [], []),
+    lang!("cpp", "C++", ["cc", "cpp", "cxx", "c++", "hh", "hpp", "hxx", "h++", "ipp"], [], [], ["c++"]),
+    lang!("csharp", "C#", ["cs", "csx"], [], [], ["c#", "cs"]),
+    lang!("css", "CSS", ["css"], [], [], []),
+    lang!("d", "D", ["d"], [], [], []),
+    lang!("dart", "Dart", ["dart"], [], [], []),
+    lang!("diff", "Diff", ["diff", "patch"], [], [], ["patch"]),
+    lang!("dockerfile", "Dockerfile", ["dockerfile", "containerfile"], ["Dockerfile", "Dockerfile.*", "Containerfile", "Containerfile.*"], [], ["docker"]),
+    lang!("elixir", "Elixir", ["ex", "exs"], [], ["elixir"], []),
+    lang!("erlang", "Erlang", ["erl", "hrl"], ["rebar.config"], ["escript"], []),
+    lang!("fish", "Fish", ["fish"], [], ["fish"], []),
+    lang!("go", "Go", ["go"], [], [], ["golang"]),
+    lang!("graphql", "GraphQL", ["graphql", "gql"], [], [], []),
+    lang!("groovy", "Groovy", ["groovy", "gradle"], ["Jenkinsfile"], ["groovy"], []),
+    lang!("haskell", "Haskell", ["hs", "lhs"], [], ["runhaskell", "stack"], []),
+    lang!("hcl", "HCL", ["hcl", "tf", "tfvars"], [], [], ["terraform"]),
+    lang!("html", "HTML", ["html", "htm", "xhtml"], [], [], []),
+    lang!("ini", "INI", ["ini", "cfg", "conf"], [".editorconfig", ".gitconfig", ".npmrc"], [], ["dosini", "conf"]),
+    lang!("java", "Java", ["java"], [], [], []),
+    lang!("javascript", "JavaScript", ["js", "mjs", "cjs", "jsx"], [], ["node", "nodejs"], ["js", "node"]),
+    lang!("json", "JSON", ["json", "jsonc", "json5", "geojson"], [".babelrc", ".eslintrc"], [], []),
+    lang!("julia", "Julia", ["jl"], [], ["julia"], []),
+    lang!("kotlin", "Kotlin", ["kt", "kts"], [], [], []),
+    lang!("latex", "LaTeX", ["tex", "sty", "cls", "ltx"], [], [], ["tex"]),
+    lang!("lisp", "Lisp", ["lisp", "lsp", "cl", "el"], [".emacs"], ["sbcl", "clisp"], ["emacs-lisp", "elisp", "common-lisp"]),
+    lang!("lua", "Lua", ["lua"], [], ["lua", "luajit"], []),
+    lang!("makefile", "Makefile", ["mk", "mak", "make"], ["Makefile", "makefile", "GNUmakefile"], ["make"], ["make", "gnumakefile", "bsdmakefile"]),
+    lang!("markdown", "Markdown", ["md", "markdown", "mdown", "mkd"], [], [], ["md", "gfm"]),
+    lang!("nix", "Nix", ["nix"], [], [], []),
+    lang!("objectivec", "Objective-C", ["m", "mm"], [], [], ["objc", "objective-c"]),
+    lang!("ocaml", "OCaml", ["ml", "mli"], [], ["ocaml"], ["tuareg"]),
+    lang!("perl", "Perl", ["pl", "pm", "t", "pod"], [], ["perl"], ["cperl"]),
+    lang!("php", "PHP", ["php", "phtml"], [], ["php"], []),
+    lang!("powershell", "PowerShell", ["ps1", "psm1", "psd1"], [], ["pwsh", "powershell"], ["pwsh", "posh"]),
+    lang!("protobuf", "Protocol Buffers", ["proto"], [], [], ["proto"]),
+    lang!("python", "Python", ["py", "pyw", "pyi"], ["SConstruct", "SConscript", "BUILD.bazel", "WORKSPACE"], ["python", "python2", "python3", "pypy", "pypy3"], ["py", "python3"]),
+    lang!("r", "R", ["r", "rmd"], [".Rprofile"], ["Rscript"], []),
+    lang!("ruby", "Ruby", ["rb", "rake", "gemspec", "ru"], ["Gemfile", "Rakefile", "Vagrantfile", "Podfile", "Brewfile"], ["ruby", "jruby"], ["rb"]),
+    lang!("rust", "Rust", ["rs"], [], ["run-cargo-script"], ["rs"]),
+    lang!("scala", "Scala", ["scala", "sc", "sbt"], [], ["scala"], []),
+    lang!("scss", "SCSS", ["scss", "sass"], [], [], ["sass"]),
+    lang!("sql", "SQL", ["sql"], [], [], []),
+    lang!("swift", "Swift", ["swift"], [], ["swift"], []),
+    lang!("tcl", "Tcl", ["tcl"], [], ["tclsh", "wish"], []),
+    lang!("toml", "TOML", ["toml"], ["Cargo.lock", "Pipfile", "poetry.lock"], [], []),
+    lang!("tsx", "TSX", ["tsx"], [], [], []),
+    lang!("typescript", "TypeScript", ["ts", "mts", "cts"], [], ["deno", "ts-node", "tsx"], ["ts"]),
+    lang!("vim", "Vim Script", ["vim"], [".vimrc", ".gvimrc", "_vimrc"], [], ["viml", "vimscript"]),
+    lang!("xml", "XML", ["xml", "xsd", "xsl", "xslt", "svg", "plist", "csproj"], [], [], []),
+    lang!("yaml", "YAML", ["yaml", "yml"], [".clang-format", ".clang-tidy"], [], ["yml"]),
+    lang!("zig", "Zig", ["zig"], [], [], []),
+    lang!("zsh", "Zsh", ["zsh"], [".zshrc", ".zshenv", ".zprofile", ".zlogin", ".zlogout"], ["zsh"], []),
+];
+
+/// Lines at either end of a file that may hold a Vim modeline.
+const MODELINE_LINES: usize = 5;
+
+static VIM_MODELINE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|\s)(?:vi|vim|ex)(?:[<=>]?\d+)?:.*?\b(?:ft|filetype|syn|syntax)=([\w.+#-]+)")
+        .unwrap()
+});
+static EMACS_MODELINE_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"-\*-(.*?)-\*-").unwrap());
+
+impl Language {
+    const fn of(def: &Definition) -> Self {
+        Self {
+            id: def.id,
+            name: def.name,
+        }
+    }
+
+    /// Detect the language of `content` read from `path`. The first of these
+    /// that names a known language wins:
+    ///
+    /// 1. `override_`, e.g. from `--pretty-syntax`;
+    /// 2. an Emacs (`-*- mode: python -*-`) or Vim (`vim: ft=python`) modeline;
+    /// 3. a `#!` line;
+    /// 4. the file name (`Dockerfile`, `Makefile`, `.bashrc`);
+    /// 5. the extension, including any that syntect has a syntax for.
+    #[must_use]
+    pub fn detect(path: &Path, content: &str, override_: Option<&str>) -> Option<Self> {
+        override_
+            .and_then(Self::find)
+            .or_else(|| Self::from_modeline(content))
+            .or_else(|| Self::from_shebang(content))
+            .or_else(|| Self::from_filename(path))
+            .or_else(|| Self::from_extension(path))
+    }
+
+    /// Look up a language by identifier, name, alias or extension, ignoring
+    /// case.
+    #[must_use]
+    pub fn find(token: &str) -> Option<Self> {
+        let token = token.trim();
+        let matches = |s: &&str| s.eq_ignore_ascii_case(token);
+        LANGUAGES
+            .iter()
+            .find(|d| {
+                matches(&d.id)
+                    || matches(&d.name)
+                    || d.aliases.iter().any(matches)
+                    || d.extensions.iter().any(matches)
+            })
+            .map(Self::of)
+    }
+
+    /// The language named in an Emacs modeline on the first line (or the
+    /// second, after a `#!` line), or in a Vim modeline near either end.
+    #[must_use]
+    pub fn from_modeline(content: &str) -> Option<Self> {
+        let lines: Vec<&str> = content.lines().collect();
+        let emacs = lines.iter().take(2).find_map(|line| {
+            let inner = EMACS_MODELINE_RE.captures(line)?.get(1)?.as_str();
+            let mode = if inner.contains(':') {
+                inner.split(';').find_map(|var| {
+                    let (key, value) = var.split_once(':')?;
+                    key.trim().eq_ignore_ascii_case("mode").then_some(value)
+                })?
+            } else {
+                inner
+            };
+            Self::find(mode.trim().trim_end_matches("-mode"))
+        });
+        let tail = lines
+            .len()
+            .saturating_sub(MODELINE_LINES)
+            .max(MODELINE_LINES);
+        emacs.or_else(|| {
+            lines
+                .iter()
+                .take(MODELINE_LINES)
+                .chain(lines.iter().skip(tail))
+                .find_map(|line| Self::find(VIM_MODELINE_RE.captures(line)?.get(1)?.as_str()))
+        })
+    }
+
+    /// The language of the interpreter in a `#!` line, looking past `env`
+    /// and its options and ignoring version numbers (`python3.12`).
+    #[must_use]
+    pub fn from_shebang(content: &str) -> Option<Self> {
+        let line = content.lines().next()?.strip_prefix("#!")?;
+        let mut words = line
+            .split_whitespace()
+            .map(|w| w.rsplit('/').next().unwrap_or(w));
+        let mut program = words.next()?;
+        if program == "env" {
+            program = words.find(|w| !w.starts_with('-') && !w.contains('='))?;
+        }
+        let unversioned = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+        [program, unversioned].into_iter().find_map(|p| {
+            LANGUAGES
+                .iter()
+                .find(|d| d.interpreters.contains(&p))
+                .map(Self::of)
+        })
+    }
+
+    /// The language of well-known file names without a telling extension.
+    #[must_use]
+    pub fn from_filename(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        LANGUAGES
+            .iter()
+            .find(|d| {
+                d.filenames.iter().any(|f| match f.strip_suffix('*') {
+                    Some(prefix) => name.len() > prefix.len() && name.starts_with(prefix),
+                    None => name == *f,
+                })
+            })
+            .map(Self::of)
+    }
+
+    /// The language of the extension of `path`, falling back to the
+    /// syntaxes bundled with syntect for extensions not listed here.
+    #[must_use]
+    pub fn from_extension(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        LANGUAGES
+            .iter()
+            .find(|d| d.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+            .map(Self::of)
+            .or_else(|| {
+                let syntax = SYNTAX_SET.find_syntax_by_extension(ext)?;
+                let plain = syntax.name == SYNTAX_SET.find_syntax_plain_text().name;
+                Some(Self {
+                    id: if plain {
+                        ""
+                    } else {
+                        syntax.file_extensions.first()?
+                    },
+                    name: &syntax.name,
+                })
+            })
+    }
+
+    /// The syntect syntax for highlighting this language, if there is one.
+    #[must_use]
+    pub fn syntax(self) -> Option<&'static SyntaxReference> {
+        let def = LANGUAGES.iter().find(|d| d.id == self.id);
+        SYNTAX_SET
+            .find_syntax_by_name(self.name)
+            .or_else(|| {
+                def.into_iter()
+                    .flat_map(|d| d.extensions.iter().chain(d.filenames))
+                    .find_map(|e| SYNTAX_SET.find_syntax_by_extension(e))
+            })
+            .or_else(|| SYNTAX_SET.find_syntax_by_token(self.id))
+    }
+}
+
+/// Name of the language of `content` read from `path`, or `Other`.
+#[must_use]
+pub fn language_name(path: &Path, content: &str) -> &'static str {
+    Language::detect(path, content, None).map_or("Other", |l| l.name)
+}
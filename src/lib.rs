@@ -16,41 +16,148 @@
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
 // Re-export everything tests need
+pub mod budget;
 pub mod cli;
 #[cfg(feature = "clipboard")]
 pub mod clipboard;
+pub mod color;
 pub mod formatters;
+pub mod highlight;
+pub mod json;
+pub mod language;
+pub mod messages;
+pub mod meta;
+pub mod minify;
+pub mod output;
+pub mod pager;
+pub mod prompt;
+pub mod rewrite;
+pub mod secrets;
+pub mod split;
+pub mod stats;
+pub mod tree;
+pub mod yaml;
 
-use crate::cli::OutputFormat;
+use crate::cli::{Decorations, HtmlStyle, LineWrap, MarkdownFence, MarkdownHeader, OutputFormat};
+use crate::color::ColorLevel;
 use crate::formatters::{
-    Formatter, ansi::Ansi, ascii::Ascii, markdown::Markdown, pretty::Pretty, utf8::Utf8, xml::Xml,
+    Formatter,
+    ansi::{Ansi, AnsiStyle},
+    ascii::Ascii,
+    documents::Documents,
+    html::Html,
+    markdown::Markdown,
+    pretty::Pretty,
+    template::Template,
+    utf8::Utf8,
+    xml::Xml,
 };
 
 impl OutputFormat {
-    pub fn into_formatter(
-        &self,
-        ansi_width: usize,
-        utf8_width: usize,
-        ln: bool,
-        pretty_syntax: Option<&str>,
-    ) -> Option<Box<dyn Formatter>> {
+    /// File extension for output in this format (used for `--split` parts).
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Xml | Self::Documents => "xml",
+            Self::Json | Self::Messages => "json",
+            Self::Jsonl => "jsonl",
+            Self::Yaml => "yaml",
+            Self::Html => "html",
+            Self::Toml => "toml",
+            Self::Ansi | Self::Ascii | Self::Utf8 | Self::Pretty | Self::Template => "txt",
+        }
+    }
+
+    /// Whether this format prints the `version`/`files`/`errors` document
+    /// described by the JSON Schema.
+    #[must_use]
+    pub const fn is_document(self) -> bool {
+        matches!(self, Self::Json | Self::Yaml | Self::Toml)
+    }
+
+    #[must_use]
+    pub fn into_formatter(&self, options: &FormatterOptions) -> Option<Box<dyn Formatter>> {
+        let ln = options.line_numbers;
         match self {
             Self::Ansi => Some(Box::new(Ansi {
-                width: ansi_width,
+                width: options.ansi_width,
+                line_numbers: ln,
+                tabs: options.tabs,
+                wrap: options.wrap,
+                color: options.color,
+                style: options.ansi_style,
+                highlight: options.ansi_highlight,
+                syntax_override: options.pretty_syntax.clone(),
+                theme: options.theme.clone(),
+            })),
+            Self::Xml => Some(Box::new(Xml {
                 line_numbers: ln,
+                cdata: options.xml_cdata,
+            })),
+            Self::Markdown => Some(Box::new(Markdown {
+                line_numbers: ln,
+                fence: options.markdown_fence,
+                header: options.markdown_header,
             })),
-            Self::Xml => Some(Box::new(Xml { line_numbers: ln })),
-            Self::Markdown => Some(Box::new(Markdown { line_numbers: ln })),
             Self::Ascii => Some(Box::new(Ascii { line_numbers: ln })),
             Self::Utf8 => Some(Box::new(Utf8 {
-                width: utf8_width,
+                width: options.utf8_width,
                 line_numbers: ln,
+                tabs: options.tabs,
+                wrap: options.wrap,
             })),
             Self::Pretty => Some(Box::new(Pretty {
                 line_numbers: ln,
-                syntax_override: pretty_syntax.map(String::from),
+                syntax_override: options.pretty_syntax.clone(),
+                theme: options.theme.clone(),
+                color: options.color,
+                decorations: options.decorations,
+            })),
+            Self::Html => Some(Box::new(Html {
+                line_numbers: ln,
+                style: options.html_style,
+                syntax_override: options.pretty_syntax.clone(),
+                theme: options.theme.clone(),
             })),
-            Self::Json => None,
+            Self::Template => options
+                .template
+                .clone()
+                .map(|t| Box::new(t) as Box<dyn Formatter>),
+            Self::Documents => Some(Box::new(Documents {
+                line_numbers: ln,
+                metadata: options.documents_metadata,
+            })),
+            Self::Json | Self::Jsonl | Self::Yaml | Self::Toml | Self::Messages => None,
         }
     }
 }
+
+/// Settings the formatters share, merged from the command line and config.
+#[derive(Debug, Clone)]
+pub struct FormatterOptions {
+    pub line_numbers: bool,
+    pub ansi_width: usize,
+    /// Styles of the 'ansi' boxes, from the `[ansi_style]` config table.
+    pub ansi_style: AnsiStyle,
+    /// Highlight the contents of the 'ansi' boxes, from `--ansi-highlight`.
+    pub ansi_highlight: bool,
+    pub utf8_width: usize,
+    /// Tab stops of the box formats, from `--tabs`.
+    pub tabs: usize,
+    /// Long lines of the box formats, from `--wrap`.
+    pub wrap: LineWrap,
+    pub pretty_syntax: Option<String>,
+    /// Theme of the highlighting formats, resolved from `--theme`.
+    pub theme: Option<String>,
+    /// Colours the terminal formats may use, resolved from `--color`.
+    pub color: ColorLevel,
+    /// What the 'pretty' format draws around files, from `--decorations`.
+    pub decorations: Decorations,
+    pub markdown_fence: MarkdownFence,
+    pub markdown_header: MarkdownHeader,
+    pub xml_cdata: bool,
+    pub html_style: HtmlStyle,
+    pub template: Option<Template>,
+    pub documents_metadata: bool,
+}
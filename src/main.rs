@@ -15,39 +15,130 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
-use rucat::cli::{Args, OutputFormat};
+use rucat::FormatterOptions;
+use rucat::budget::{self, Candidate, Verdict};
+use rucat::cli::{
+    Args, ColorMode, Decorations, HtmlStyle, LineWrap, MarkdownFence, MarkdownHeader, MessagesApi,
+    MessagesContent, MetaField, OutputFormat, Paging, SecretsMode, StatsMode, TreeInfo,
+};
 #[cfg(feature = "clipboard")]
 use rucat::clipboard::ClipboardProvider;
+use rucat::color::ColorLevel;
+use rucat::formatters::{FileMeta, Formatter, ansi::AnsiStyle, template::Template};
+use rucat::highlight;
+use rucat::json::{self, Document, FileEntry, FileError, Line};
+use rucat::messages::Envelope;
+use rucat::meta::Metadata;
+use rucat::minify::Minified;
+use rucat::output::{self, Index, Mirror};
+use rucat::pager::{self, Paged};
+use rucat::prompt::Prompt;
+use rucat::rewrite::{RewriteRule, Rewriter};
+use rucat::secrets::{self, Scanner};
+use rucat::split::{self, SplitLimit};
+use rucat::stats::Stats;
+use rucat::tree::Tree;
+use rucat::yaml;
 use serde::Deserialize;
+use std::borrow::Cow;
 use std::fs;
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, IsTerminal, Read, Write};
+use std::mem;
 #[cfg(unix)]
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// The controlling terminal, for questions when stdin is taken.
+#[cfg(windows)]
+const TTY: &str = "CONIN$";
+#[cfg(not(windows))]
+const TTY: &str = "/dev/tty";
+
 #[derive(Deserialize, Default)]
 struct Config {
     format: Option<OutputFormat>,
     numbers: Option<bool>,
     strip: Option<usize>,
+    meta: Option<Vec<MetaField>>,
     ansi_width: Option<usize>,
+    ansi_style: Option<AnsiStyle>,
+    ansi_highlight: Option<bool>,
     utf8_width: Option<usize>,
+    tabs: Option<usize>,
+    wrap: Option<LineWrap>,
     pretty_syntax: Option<String>,
+    color: Option<ColorMode>,
+    paging: Option<Paging>,
+    decorations: Option<Decorations>,
+    theme: Option<String>,
+    theme_light: Option<String>,
+    theme_dark: Option<String>,
+    markdown_fence: Option<MarkdownFence>,
+    markdown_header: Option<MarkdownHeader>,
+    xml_cdata: Option<bool>,
+    html_style: Option<HtmlStyle>,
+    documents_metadata: Option<bool>,
+    budget: Option<String>,
+    tree: Option<bool>,
+    tree_info: Option<TreeInfo>,
+    stats: Option<StatsMode>,
+    secrets: Option<SecretsMode>,
+    secrets_allowlist: Option<PathBuf>,
+    #[serde(default)]
+    rewrite: Vec<RewriteRule>,
+    strip_comments: Option<bool>,
+    strip_doc_comments: Option<bool>,
+    minify_whitespace: Option<bool>,
+    prompt: Option<String>,
+    template: Option<String>,
+    split: Option<String>,
+    messages_api: Option<MessagesApi>,
+    messages_content: Option<MessagesContent>,
+    system: Option<String>,
+    model: Option<String>,
 }
 
 struct FormattingOptions<'a> {
     format: OutputFormat,
-    line_numbers: bool,
+    formatter: FormatterOptions,
     strip: usize,
-    pretty_syntax: Option<&'a str>,
-    ansi_width: usize,
-    utf8_width: usize,
+    meta: Vec<MetaField>,
+    budget: Option<usize>,
+    must: &'a [PathBuf],
+    tree: Option<TreeInfo>,
+    stats: Option<StatsMode>,
+    secrets: SecretsMode,
+    scanner: Option<&'a Scanner>,
+    copying: bool,
+    rewriter: Option<&'a Rewriter>,
+    minify: Minify,
+    prompt: Prompt,
+    split: Option<Split<'a>>,
+    /// Write each file to its own file in this directory (`--output-dir`).
+    output_dir: Option<&'a Path>,
+    messages: Option<Envelope>,
+}
+
+impl FormattingOptions<'_> {
+    /// Whether secrets that were found stop rucat: with `--secrets refuse`,
+    /// and with `--copy` unless they are redacted or scanning is off.
+    fn refuses_secrets(&self) -> bool {
+        self.secrets == SecretsMode::Refuse || (self.copying && self.secrets == SecretsMode::Warn)
+    }
+}
+
+/// What `--strip-comments` and `--minify-whitespace` remove.
+#[derive(Clone, Copy)]
+#[allow(clippy::struct_excessive_bools)]
+struct Minify {
+    comments: bool,
+    doc_comments: bool,
+    whitespace: bool,
 }
 
 fn load_config() -> Config {
-    if let Some(mut path) = dirs::config_dir() {
-        path.push("rucat");
+    if let Some(mut path) = config_dir() {
         path.push("config.toml");
         if path.exists() {
             let content = fs::read_to_string(path).unwrap_or_default();
@@ -57,72 +148,255 @@ fn load_config() -> Config {
     Config::default()
 }
 
-// Struct for JSON output
-#[derive(serde::Serialize)]
-struct FileEntry {
-    path: String,
+/// rucat's directory under the user's config directory (e.g. `~/.config/rucat`).
+fn config_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|p| p.join("rucat"))
+}
+
+/// Print what `--json-schema`, `--list-themes` or `--list-syntaxes` asked
+/// for. Returns whether there was anything to print, in which case rucat
+/// exits without reading files.
+fn print_info(args: &Args) -> bool {
+    if args.json_schema {
+        print!("{}", json::SCHEMA);
+    } else if args.list_themes {
+        for name in highlight::theme_names() {
+            println!("{name}");
+        }
+    } else if args.list_syntaxes {
+        for name in highlight::syntax_names() {
+            println!("{name}");
+        }
+    } else {
+        return false;
+    }
+    true
+}
+
+/// How `--split` breaks up the output.
+struct Split<'a> {
+    limit: SplitLimit,
+    /// Write the parts to files in this directory instead of stdout.
+    dir: Option<&'a Path>,
+    /// Ask for confirmation on the terminal rather than stdin, which held
+    /// the input.
+    confirm_on_tty: bool,
+}
+
+/// A file that was read successfully, tagged with the position of the
+/// command-line argument it came from.
+#[derive(Clone)]
+struct Source {
+    path: PathBuf,
+    /// Path shown in the output (after `--strip` and `--rewrite`).
+    display: PathBuf,
+    /// Path as given, after `--rewrite` but not `--strip`.
+    given: PathBuf,
+    rank: usize,
     content: String,
+    /// Original line numbers of `content`, once lines have been removed.
+    lines: Option<Vec<usize>>,
+    /// `--meta` fields, gathered when the file was read.
+    meta: Metadata,
 }
 
-fn process_stdin(
-    options: &FormattingOptions,
-    #[cfg(feature = "clipboard")] clipboard_buffer: &mut Option<Vec<u8>>,
-) -> anyhow::Result<()> {
-    let mut buf = String::new();
-    io::stdin().read_to_string(&mut buf)?;
-    let pseudo = PathBuf::from("-");
+impl Source {
+    fn entry(&self) -> FileEntry {
+        FileEntry {
+            path: self.display.display().to_string(),
+            meta: self.meta.clone(),
+            content: self.content.clone(),
+        }
+    }
+}
 
-    let fmt = options.format.into_formatter(
-        options.ansi_width,
-        options.utf8_width,
-        options.line_numbers,
-        options.pretty_syntax,
-    );
+/// Everything rucat prints goes through here: it is written to stdout, or
+/// a pager, and with `--copy`, also collected for the clipboard.
+struct Output {
+    /// Stdout or the pager; `None` with `--output` (but not `--tee`) and
+    /// `--output-dir`.
+    sink: Option<Paged<io::Stdout>>,
+    /// The `--output` file and what is to be written to it.
+    file: Option<(PathBuf, Vec<u8>)>,
+    #[cfg(feature = "clipboard")]
+    clipboard: Option<(ClipboardProvider, Vec<u8>)>,
+    /// Reports for stderr (statistics, secrets, rewrites), held back until
+    /// the pager has given the terminal back.
+    reports: Vec<u8>,
+}
 
-    if let Some(ref f) = fmt {
-        let disp = strip_components(&pseudo, options.strip);
+impl Output {
+    /// Copy what has been collected so far to the clipboard and start
+    /// collecting afresh. Returns whether anything was copied.
+    #[cfg(feature = "clipboard")]
+    fn copy_collected(&mut self) -> io::Result<bool> {
+        let Some((provider, buffer)) = &mut self.clipboard else {
+            return Ok(false);
+        };
+        if buffer.is_empty() {
+            return Ok(false);
+        }
+        provider.copy_to_clipboard(&String::from_utf8_lossy(buffer), &mut io::stdout())?;
+        buffer.clear();
+        Ok(true)
+    }
 
-        #[cfg(feature = "clipboard")]
-        if let Some(cb) = clipboard_buffer {
-            f.write(&disp, &buf, cb)?;
-            f.write(&disp, &buf, &mut io::stdout())?;
-        } else {
-            f.write(&disp, &buf, &mut io::stdout())?;
+    #[cfg(not(feature = "clipboard"))]
+    #[allow(clippy::unnecessary_wraps, clippy::unused_self)]
+    const fn copy_collected(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    /// Wait for the pager, replace the `--output` file with what was
+    /// written and print the reports. Nothing reaches the file if rucat
+    /// stopped before this.
+    fn finish(mut self) -> anyhow::Result<()> {
+        if let Some(sink) = self.sink.take() {
+            sink.finish()?;
         }
+        if let Some((path, buf)) = &self.file {
+            output::write_atomic(path, buf)
+                .map_err(|e| anyhow::anyhow!("cannot write {}: {e}", path.display()))?;
+        }
+        io::stderr().write_all(&mem::take(&mut self.reports))?;
+        Ok(())
+    }
+}
 
-        #[cfg(not(feature = "clipboard"))]
-        f.write(&disp, &buf, &mut io::stdout())?;
-    } else {
-        let mut file_entries = Vec::new();
-        let disp = strip_components(&pseudo, options.strip);
-        file_entries.push(FileEntry {
-            path: disp.display().to_string(),
-            content: buf,
-        });
+impl Drop for Output {
+    /// Print the reports when rucat stops early, after the pager.
+    fn drop(&mut self) {
+        drop(self.sink.take());
+        let _ = io::stderr().write_all(&self.reports);
+    }
+}
 
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(sink) = &mut self.sink {
+            sink.write_all(buf)?;
+        }
+        if let Some((_, file)) = &mut self.file {
+            file.extend_from_slice(buf);
+        }
         #[cfg(feature = "clipboard")]
-        if let Some(cb) = clipboard_buffer {
-            let json_output = serde_json::to_string_pretty(&file_entries)?;
-            write!(cb, "{json_output}")?;
-            writeln!(io::stdout(), "{json_output}")?;
-        } else {
-            format_json(&file_entries)?;
+        if let Some((_, cb)) = &mut self.clipboard {
+            cb.extend_from_slice(buf);
         }
+        Ok(buf.len())
+    }
 
-        #[cfg(not(feature = "clipboard"))]
-        format_json(&file_entries)?;
+    fn flush(&mut self) -> io::Result<()> {
+        self.sink.as_mut().map_or(Ok(()), Write::flush)
     }
-    Ok(())
+}
+
+/// Where output goes: through the pager when `paging` asks for it, unless
+/// `--copy` has escape sequences that must reach the terminal itself.
+fn output_sink(paging: Paging, copying: bool) -> Paged<io::Stdout> {
+    let stdout = io::stdout();
+    let height = match paging {
+        _ if copying => None,
+        Paging::Never => None,
+        Paging::Always => Some(0),
+        Paging::Auto => stdout.is_terminal().then(|| {
+            terminal_size::terminal_size()
+                .map(|(_, terminal_size::Height(h))| usize::from(h))
+                .filter(|&h| h > 0)
+                .unwrap_or(24)
+        }),
+    };
+    match (height, pager::command(|name| std::env::var(name).ok())) {
+        (Some(height), Some(command)) => Paged::new(stdout, command, height),
+        _ => Paged::direct(stdout),
+    }
+}
+
+fn process_stdin(options: &FormattingOptions, out: &mut Output) -> anyhow::Result<()> {
+    let mut buf = String::new();
+    io::stdin().read_to_string(&mut buf)?;
+    let pseudo = PathBuf::from("-");
+    let source = Source {
+        display: strip_components(&pseudo, options.strip),
+        given: pseudo.clone(),
+        meta: Metadata::collect(&options.meta, &pseudo, &buf, None),
+        path: pseudo,
+        rank: 0,
+        content: buf,
+        lines: None,
+    };
+    render(vec![source], &[], options, out)
 }
 
 fn process_files(
     files: &[PathBuf],
     options: &FormattingOptions,
-    #[cfg(feature = "clipboard")] clipboard_buffer: &mut Option<Vec<u8>>,
+    out: &mut Output,
+) -> anyhow::Result<()> {
+    let paths = expand_dirs(files);
+    if can_stream(options) {
+        return stream_json_lines(paths, options, out);
+    }
+    let mut sources = Vec::with_capacity(paths.len());
+    let mut errors = Vec::new();
+    for (rank, path) in paths {
+        match read_source(path, rank, options) {
+            Ok(source) => sources.push(source),
+            Err(e) => errors.push(e),
+        }
+    }
+    render(sources, &errors, options, out)
+}
+
+/// Whether `--format jsonl` can write each file as soon as it is read:
+/// nothing asks for all of them first (`--budget`, `--split`, `--tree`,
+/// `--stats`), and a refusal over secrets cannot stop after output has begun.
+fn can_stream(options: &FormattingOptions) -> bool {
+    options.format == OutputFormat::Jsonl
+        && options.budget.is_none()
+        && options.split.is_none()
+        && options.tree.is_none()
+        && options.stats.is_none()
+        && !options.refuses_secrets()
+}
+
+/// Write a `--format jsonl` line for each file as soon as it has been read,
+/// so large trees can be piped into other tools without waiting.
+fn stream_json_lines(
+    paths: Vec<(usize, PathBuf)>,
+    options: &FormattingOptions,
+    out: &mut Output,
 ) -> anyhow::Result<()> {
-    // Expand directories to individual files
-    let mut paths = Vec::<PathBuf>::new();
-    for p in files {
+    let mut report = Vec::new();
+    if let Some(text) = options.prompt.before.as_deref() {
+        writeln!(out, "{}", Line::Prompt { text }.to_json()?)?;
+    }
+    for (rank, path) in paths {
+        let line = match read_source(path, rank, options) {
+            Ok(mut source) => {
+                scan_source(&mut source, options, &mut report);
+                minify_source(&mut source, options.minify);
+                rewrite_source(&mut source, options);
+                Line::File(&source.entry()).to_json()?
+            }
+            Err(e) => Line::Error(&e).to_json()?,
+        };
+        writeln!(out, "{line}")?;
+        out.flush()?;
+    }
+    if let Some(text) = options.prompt.after.as_deref() {
+        writeln!(out, "{}", Line::PromptAfter { text }.to_json()?)?;
+    }
+    report_secrets(&report, options, &mut out.reports)?;
+    report_rewrites(options, &mut out.reports)?;
+    Ok(())
+}
+
+/// Expand directories to the files under them, each tagged with the
+/// position of the command-line argument it came from.
+fn expand_dirs(files: &[PathBuf]) -> Vec<(usize, PathBuf)> {
+    let mut paths = Vec::<(usize, PathBuf)>::new();
+    for (rank, p) in files.iter().enumerate() {
         if p.is_dir() {
             for entry in WalkDir::new(p)
                 .min_depth(1)
@@ -130,70 +404,746 @@ fn process_files(
                 .filter_map(Result::ok)
                 .filter(|e| e.file_type().is_file())
             {
-                paths.push(entry.into_path());
+                paths.push((rank, entry.into_path()));
             }
         } else {
-            paths.push(p.clone());
+            paths.push((rank, p.clone()));
         }
     }
 
-    let fmt = options.format.into_formatter(
-        options.ansi_width,
-        options.utf8_width,
-        options.line_numbers,
-        options.pretty_syntax,
-    );
+    paths
+}
 
-    if options.format == OutputFormat::Json {
-        let entries: Vec<FileEntry> = paths
-            .iter()
-            .filter_map(|p| read_file_content(p).ok().map(|c| (p, c)))
-            .map(|(p, content)| {
-                let display_path = strip_components(p, options.strip);
-                FileEntry {
-                    path: display_path.display().to_string(),
-                    content,
-                }
+/// Read the file at `path`, reporting on stderr if it cannot be read.
+fn read_source(
+    path: PathBuf,
+    rank: usize,
+    options: &FormattingOptions,
+) -> Result<Source, FileError> {
+    match read_file_content(&path) {
+        Ok(content) => Ok(Source {
+            display: strip_components(&path, options.strip),
+            given: path.clone(),
+            meta: Metadata::collect(
+                &options.meta,
+                &path,
+                &content,
+                fs::metadata(&path).ok().as_ref(),
+            ),
+            path,
+            rank,
+            content,
+            lines: None,
+        }),
+        Err(e) => {
+            eprintln!("Error reading {}: {}", path.display(), e);
+            let shown = strip_components(&path, options.strip)
+                .to_string_lossy()
+                .into_owned();
+            Err(FileError {
+                path: match options.rewriter {
+                    Some(rewriter) => rewriter.apply(&path, &shown).into_owned(),
+                    None => shown,
+                },
+                error: e.to_string(),
             })
-            .collect();
+        }
+    }
+}
 
-        #[cfg(feature = "clipboard")]
-        if let Some(cb) = clipboard_buffer {
-            let json_output = serde_json::to_string_pretty(&entries)?;
-            write!(cb, "{json_output}")?;
-            writeln!(io::stdout(), "{json_output}")?;
+fn render(
+    sources: Vec<Source>,
+    errors: &[FileError],
+    options: &FormattingOptions,
+    out: &mut Output,
+) -> anyhow::Result<()> {
+    let sources = scan_secrets(sources, options, &mut out.reports)?;
+    let sources = minify(sources, options.minify);
+    let sources = apply_rewrites(sources, options, &mut out.reports)?;
+    let fmt = options.format.into_formatter(&options.formatter);
+    let collected = sources.len();
+    let (sources, note) = match options.budget {
+        Some(limit) => apply_budget(sources, limit, errors, fmt.as_deref(), options)?,
+        None => (sources, None),
+    };
+    let skipped = errors.len() + collected - sources.len();
+    let extras = Extras::collect(&sources, skipped, options);
+    let bundle = Bundle::new(&sources, &extras, errors, note.as_deref(), options);
+    match (&options.split, options.output_dir) {
+        (_, Some(dir)) => write_dir(&bundle, fmt.as_deref(), options, dir)?,
+        (Some(split), None) => write_parts(&bundle, fmt.as_deref(), options, split, out)?,
+        (None, None) => write_bundle(&bundle, fmt.as_deref(), options, out)?,
+    }
+    // JSON has nowhere to carry the note, so report it on stderr instead.
+    if fmt.is_none()
+        && options.messages.is_none()
+        && let Some(note) = note
+    {
+        writeln!(out.reports, "Warning: {note}")?;
+    }
+
+    match (options.stats, &extras.stats) {
+        (Some(StatsMode::Stderr), Some(stats)) => {
+            for line in stats.lines() {
+                writeln!(out.reports, "{line}")?;
+            }
+        }
+        (Some(StatsMode::Json), Some(stats)) => {
+            writeln!(out.reports, "{}", serde_json::to_string_pretty(stats)?)?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// The overview and statistics of the files that made it into the output.
+struct Extras {
+    tree: Option<Tree>,
+    stats: Option<Stats>,
+}
+
+impl Extras {
+    fn collect(sources: &[Source], skipped: usize, options: &FormattingOptions) -> Self {
+        let files = || {
+            sources
+                .iter()
+                .map(|s| (s.display.as_path(), s.content.as_str()))
+        };
+        // The JSON, YAML and TOML documents always carry the statistics.
+        let stats = (options.stats.is_some() || options.format.is_document())
+            .then(|| Stats::collect(files(), skipped));
+        Self {
+            tree: options.tree.map(|info| Tree::build(files(), info)),
+            stats,
+        }
+    }
+}
+
+/// Everything that goes into one document: the whole output, or one part of
+/// it with `--split`.
+#[derive(Clone, Copy)]
+struct Bundle<'a> {
+    sources: &'a [Source],
+    /// Position of the first source in the whole output.
+    first_index: usize,
+    tree: Option<&'a Tree>,
+    footer: Option<&'a Stats>,
+    /// Files that could not be read, for the JSON formats.
+    errors: &'a [FileError],
+    note: Option<&'a str>,
+    prompt_before: Option<&'a str>,
+    prompt_after: Option<&'a str>,
+    /// `(part, total)` with `--split`.
+    part: Option<(usize, usize)>,
+}
+
+impl<'a> Bundle<'a> {
+    /// The whole output: `sources` with everything that goes around them.
+    fn new(
+        sources: &'a [Source],
+        extras: &'a Extras,
+        errors: &'a [FileError],
+        note: Option<&'a str>,
+        options: &'a FormattingOptions,
+    ) -> Self {
+        let footer = extras
+            .stats
+            .as_ref()
+            .filter(|_| options.format.is_document() || options.stats == Some(StatsMode::Footer));
+        Self {
+            sources,
+            first_index: 1,
+            tree: extras.tree.as_ref(),
+            footer,
+            errors,
+            note,
+            prompt_before: options.prompt.before.as_deref(),
+            prompt_after: options.prompt.after.as_deref(),
+            part: None,
+        }
+    }
+}
+
+/// Write `bundle` with `fmt`, or as data when there is no formatter: a chat
+/// API request body with `--format messages`, one JSON object per line with
+/// `--format jsonl`, and a single JSON, YAML or TOML document otherwise.
+fn write_bundle(
+    bundle: &Bundle,
+    fmt: Option<&dyn Formatter>,
+    options: &FormattingOptions,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    let Some(f) = fmt else {
+        let entries: Vec<FileEntry> = bundle.sources.iter().map(Source::entry).collect();
+        if let Some(envelope) = &options.messages {
+            let blocks = message_blocks(bundle, &entries);
+            writeln!(out, "{}", envelope.to_json(&blocks)?)?;
+        } else if options.format == OutputFormat::Jsonl {
+            for line in json_lines(bundle, &entries) {
+                writeln!(out, "{}", line.to_json()?)?;
+            }
         } else {
-            format_json(&entries)?;
+            let document = Document {
+                version: json::VERSION,
+                part: bundle.part.map(|(part, total)| format!("{part}/{total}")),
+                prompt: bundle.prompt_before,
+                tree: bundle.tree,
+                files: &entries,
+                errors: bundle.errors,
+                stats: bundle.footer,
+                prompt_after: bundle.prompt_after,
+            };
+            match options.format {
+                OutputFormat::Yaml => write!(out, "{}", yaml::to_string(&document)?)?,
+                OutputFormat::Toml => write!(out, "{}", toml::to_string_pretty(&document)?)?,
+                _ => writeln!(out, "{}", serde_json::to_string_pretty(&document)?)?,
+            }
         }
+        return Ok(());
+    };
 
-        #[cfg(not(feature = "clipboard"))]
-        format_json(&entries)?;
-    } else {
-        for p in paths {
-            let result = read_file_content(&p);
-            match result {
-                Ok(content) => {
-                    let display_path = strip_components(&p, options.strip);
-                    if let Some(ref f) = fmt {
-                        #[cfg(feature = "clipboard")]
-                        if let Some(cb) = clipboard_buffer {
-                            f.write(&display_path, &content, cb)?;
-                            f.write(&display_path, &content, &mut io::stdout())?;
-                        } else {
-                            f.write(&display_path, &content, &mut io::stdout())?;
-                        }
-
-                        #[cfg(not(feature = "clipboard"))]
-                        f.write(&display_path, &content, &mut io::stdout())?;
-                    }
-                }
-                Err(e) => writeln!(io::stderr(), "Error reading {}: {}", p.display(), e)?,
+    f.begin_document(out)?;
+    if let Some((part, total)) = bundle.part {
+        f.write_note(&format!("Part {part}/{total}"), out)?;
+    }
+    if let Some(text) = bundle.prompt_before {
+        f.write_preamble(text, out)?;
+    }
+    let index: Vec<(usize, &Path)> = bundle
+        .sources
+        .iter()
+        .enumerate()
+        .map(|(idx, s)| (bundle.first_index + idx, s.display.as_path()))
+        .collect();
+    f.write_index(&index, out)?;
+    if let Some(tree) = bundle.tree {
+        f.write_tree(tree, out)?;
+    }
+    for (idx, s) in bundle.sources.iter().enumerate() {
+        let meta = FileMeta {
+            source: Some(&s.given),
+            index: bundle.first_index + idx,
+            line_numbers: s.lines.as_deref(),
+            metadata: Some(&s.meta),
+        };
+        f.write_file(&s.display, &s.content, &meta, out)?;
+    }
+    if let Some(stats) = bundle.footer {
+        f.write_stats(stats, out)?;
+    }
+    if let Some(note) = bundle.note {
+        f.write_note(note, out)?;
+    }
+    if let Some(text) = bundle.prompt_after {
+        f.write_postamble(text, out)?;
+    }
+    f.end_document(out)?;
+    Ok(())
+}
+
+/// The lines of `--format jsonl`, in the order the other formats print
+/// their parts.
+fn json_lines<'a>(bundle: &Bundle<'a>, entries: &'a [FileEntry]) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    if let Some((part, total)) = bundle.part {
+        lines.push(Line::Part {
+            part: format!("{part}/{total}"),
+        });
+    }
+    lines.extend(bundle.prompt_before.map(|text| Line::Prompt { text }));
+    lines.extend(bundle.tree.map(|tree| Line::Tree { tree }));
+    lines.extend(entries.iter().map(Line::File));
+    lines.extend(bundle.errors.iter().map(Line::Error));
+    lines.extend(bundle.footer.map(Line::Stats));
+    lines.extend(bundle.prompt_after.map(|text| Line::PromptAfter { text }));
+    lines
+}
+
+/// The text blocks of the user message of `--format messages`, in the order
+/// the other formats print them.
+fn message_blocks(bundle: &Bundle, entries: &[FileEntry]) -> Vec<String> {
+    let mut blocks = Vec::new();
+    if let Some((part, total)) = bundle.part {
+        blocks.push(format!("Part {part}/{total}"));
+    }
+    blocks.extend(bundle.prompt_before.map(str::to_owned));
+    if let Some(tree) = bundle.tree {
+        blocks.push(tree.lines(true).join("\n"));
+    }
+    for entry in entries {
+        blocks.push(format!(
+            "File: {}\n\n{}",
+            entry.meta.label(Path::new(&entry.path)),
+            entry.content
+        ));
+    }
+    if let Some(stats) = bundle.footer {
+        blocks.push(stats.lines().join("\n"));
+    }
+    blocks.extend(bundle.note.map(|note| format!("[rucat] {note}")));
+    blocks.extend(bundle.prompt_after.map(str::to_owned));
+    blocks
+}
+
+/// Break `bundle` into parts of at most `split.limit` and write them to
+/// `split.dir`, or one after another to `out`. With `--copy` each part is
+/// copied to the clipboard in turn, waiting for Enter in between.
+fn write_parts(
+    bundle: &Bundle,
+    fmt: Option<&dyn Formatter>,
+    options: &FormattingOptions,
+    split: &Split,
+    out: &mut Output,
+) -> anyhow::Result<()> {
+    let limit = split.limit;
+    let measure = |b: &Bundle| -> anyhow::Result<usize> {
+        let mut buf = Vec::new();
+        write_bundle(b, fmt, options, &mut buf)?;
+        Ok(limit.measure(&String::from_utf8_lossy(&buf)))
+    };
+    // Every part is budgeted as if it carried all the extras.
+    let empty = Bundle {
+        sources: &[],
+        part: Some((99, 99)),
+        ..*bundle
+    };
+    let base = measure(&empty)?;
+    if base >= limit.limit() {
+        anyhow::bail!(
+            "--split limit of {} {} leaves no room for files after the tree, prompt and notes ({base} {})",
+            limit.limit(),
+            limit.unit(),
+            limit.unit()
+        );
+    }
+    let room = limit.limit() - base;
+    let cost = |s: &Source| -> anyhow::Result<usize> {
+        let one = Bundle {
+            sources: std::slice::from_ref(s),
+            ..empty
+        };
+        Ok(measure(&one)?.saturating_sub(base))
+    };
+
+    let mut parts: Vec<Vec<Source>> = Vec::new();
+    let mut current = Vec::new();
+    let mut used = 0;
+    for source in bundle.sources {
+        let whole = cost(source)?;
+        let pieces = if whole <= room {
+            vec![(source.clone(), whole)]
+        } else {
+            let lines: Vec<&str> = source.content.lines().collect();
+            let fits = |r: std::ops::Range<usize>| {
+                cost(&slice_source(source, &lines, r)).is_ok_and(|c| c <= room)
+            };
+            split::split_lines(&lines, fits)
+                .into_iter()
+                .map(|r| {
+                    let piece = slice_source(source, &lines, r);
+                    cost(&piece).map(|c| (piece, c))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?
+        };
+        for (piece, c) in pieces {
+            if !current.is_empty() && used + c > room {
+                parts.push(std::mem::take(&mut current));
+                used = 0;
+            }
+            current.push(piece);
+            used += c;
+        }
+    }
+    if !current.is_empty() || parts.is_empty() {
+        parts.push(current);
+    }
+
+    let total = parts.len();
+    let digits = total.to_string().len();
+    let mut first_index = 1;
+    for (idx, sources) in parts.iter().enumerate() {
+        let (first, last) = (idx == 0, idx + 1 == total);
+        let part = Bundle {
+            sources,
+            first_index,
+            tree: bundle.tree.filter(|_| first),
+            footer: bundle.footer.filter(|_| last),
+            errors: if first { bundle.errors } else { &[] },
+            note: bundle.note.filter(|_| last),
+            prompt_before: bundle.prompt_before.filter(|_| first),
+            prompt_after: bundle.prompt_after.filter(|_| last),
+            part: Some((idx + 1, total)),
+        };
+        first_index += sources.len();
+        if let Some(dir) = split.dir {
+            fs::create_dir_all(dir)?;
+            let path = dir.join(format!(
+                "part-{:0digits$}.{}",
+                idx + 1,
+                options.format.extension()
+            ));
+            let mut buf = Vec::new();
+            write_bundle(&part, fmt, options, &mut buf)?;
+            fs::write(&path, buf)
+                .map_err(|e| anyhow::anyhow!("cannot write {}: {e}", path.display()))?;
+        } else {
+            write_bundle(&part, fmt, options, out)?;
+            if out.copy_collected()? && !last {
+                confirm_next_part(idx + 1, total, split.confirm_on_tty)?;
             }
         }
     }
+    if let Some(dir) = split.dir {
+        writeln!(io::stderr(), "Wrote {total} part(s) to {}", dir.display())?;
+    }
+    Ok(())
+}
+
+/// Write each source of `bundle` to its own file in `dir`, at its shown path
+/// plus the format's extension, and an index of them beside the files.
+fn write_dir(
+    bundle: &Bundle,
+    fmt: Option<&dyn Formatter>,
+    options: &FormattingOptions,
+    dir: &Path,
+) -> anyhow::Result<()> {
+    let write = |path: &Path, buf: &[u8]| -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        output::write_atomic(path, buf)
+            .map_err(|e| anyhow::anyhow!("cannot write {}: {e}", path.display()))
+    };
+    let mut mirror = Mirror::new();
+    let mut files = Vec::new();
+    for (idx, source) in bundle.sources.iter().enumerate() {
+        let one = Bundle {
+            sources: std::slice::from_ref(source),
+            first_index: bundle.first_index + idx,
+            tree: None,
+            footer: None,
+            errors: &[],
+            note: None,
+            prompt_before: None,
+            prompt_after: None,
+            part: None,
+        };
+        let mut buf = Vec::new();
+        write_bundle(&one, fmt, options, &mut buf)?;
+        let relative = mirror.path(&source.display, options.format.extension());
+        write(&dir.join(&relative), &buf)?;
+        files.push((source.display.as_path(), relative));
+    }
+    let total = files.len();
+    let index = Index {
+        prompt: bundle.prompt_before,
+        tree: bundle.tree,
+        files,
+        errors: bundle.errors,
+        stats: bundle.footer,
+        note: bundle.note,
+        prompt_after: bundle.prompt_after,
+    };
+    write(&dir.join(output::INDEX), index.to_markdown().as_bytes())?;
+    writeln!(io::stderr(), "Wrote {total} file(s) to {}", dir.display())?;
+    Ok(())
+}
+
+/// Lines `range` of `source` as a source of their own, keeping their
+/// original line numbers.
+fn slice_source(source: &Source, lines: &[&str], range: std::ops::Range<usize>) -> Source {
+    let numbers = range
+        .clone()
+        .map(|idx| source.lines.as_ref().map_or(idx + 1, |n| n[idx]))
+        .collect();
+    let mut content = lines[range].join("\n");
+    content.push('\n');
+    Source {
+        content,
+        lines: Some(numbers),
+        ..source.clone()
+    }
+}
+
+/// Wait for Enter before the next part goes to the clipboard. The answer is
+/// read from the terminal when stdin carried the input, from stdin otherwise.
+fn confirm_next_part(copied: usize, total: usize, tty: bool) -> io::Result<()> {
+    write!(
+        io::stderr(),
+        "Part {copied}/{total} copied to the clipboard. Press Enter to copy part {}...",
+        copied + 1
+    )?;
+    let mut answer = String::new();
+    if tty {
+        io::BufReader::new(fs::File::open(TTY)?).read_line(&mut answer)?;
+    } else {
+        io::stdin().read_line(&mut answer)?;
+    }
+    Ok(())
+}
+
+/// Look for secrets in every source and redact, report or refuse them
+/// according to `--secrets`.
+fn scan_secrets(
+    mut sources: Vec<Source>,
+    options: &FormattingOptions,
+    err: &mut dyn Write,
+) -> anyhow::Result<Vec<Source>> {
+    let mut report = Vec::new();
+    for s in &mut sources {
+        scan_source(s, options, &mut report);
+    }
+    report_secrets(&report, options, err)?;
+    Ok(sources)
+}
+
+/// Look for secrets in one source, redacting them if asked to and adding
+/// a line per finding to `report`.
+fn scan_source(s: &mut Source, options: &FormattingOptions, report: &mut Vec<String>) {
+    let Some(scanner) = options.scanner else {
+        return;
+    };
+    let findings = scanner.scan(&s.content);
+    for f in &findings {
+        report.push(format!("  {}:{} {}", s.display.display(), f.line, f.kind));
+    }
+    if !findings.is_empty() && options.secrets == SecretsMode::Redact {
+        s.content = secrets::redact(&s.content, &findings);
+    }
+}
+
+/// Tell the user about the secrets in `report` on `err`, or refuse to go on.
+fn report_secrets(
+    report: &[String],
+    options: &FormattingOptions,
+    err: &mut dyn Write,
+) -> anyhow::Result<()> {
+    if report.is_empty() {
+        return Ok(());
+    }
+
+    let count = report.len();
+    let report = report.join("\n");
+    match options.secrets {
+        SecretsMode::Refuse => anyhow::bail!(
+            "refusing to print {count} possible secret(s) (use --secrets-allowlist for false positives):\n{report}"
+        ),
+        SecretsMode::Warn if options.copying => anyhow::bail!(
+            "refusing to copy {count} possible secret(s) (use --secrets redact to copy them redacted, or --secrets-allowlist for false positives):\n{report}"
+        ),
+        SecretsMode::Warn => writeln!(
+            err,
+            "Warning: {count} possible secret(s) left in the output:\n{report}"
+        )?,
+        SecretsMode::Redact | SecretsMode::Off => {
+            writeln!(err, "Redacted {count} possible secret(s):\n{report}")?;
+        }
+    }
     Ok(())
 }
 
+/// Remove comments and blank lines as requested by `--strip-comments` and
+/// `--minify-whitespace`, remembering the original line numbers.
+fn minify(mut sources: Vec<Source>, minify: Minify) -> Vec<Source> {
+    for s in &mut sources {
+        minify_source(s, minify);
+    }
+    sources
+}
+
+fn minify_source(s: &mut Source, minify: Minify) {
+    if !minify.comments && !minify.whitespace {
+        return;
+    }
+    let mut m = Minified::new(&s.content);
+    if minify.comments {
+        m = m.strip_comments(&s.path, !minify.doc_comments);
+    }
+    if minify.whitespace {
+        m = m.minify_whitespace();
+    }
+    s.content = m.content;
+    s.lines = Some(m.lines);
+}
+
+/// Apply `--rewrite` rules to every body and display path, then report on
+/// `err` how many substitutions were made.
+fn apply_rewrites(
+    mut sources: Vec<Source>,
+    options: &FormattingOptions,
+    err: &mut dyn Write,
+) -> io::Result<Vec<Source>> {
+    for s in &mut sources {
+        rewrite_source(s, options);
+    }
+    report_rewrites(options, err)?;
+    Ok(sources)
+}
+
+fn rewrite_source(s: &mut Source, options: &FormattingOptions) {
+    let Some(rewriter) = options.rewriter else {
+        return;
+    };
+    if let Cow::Owned(content) = rewriter.apply(&s.path, &s.content) {
+        s.content = content;
+    }
+    if let Cow::Owned(display) = rewriter.apply(&s.path, &s.display.to_string_lossy()) {
+        s.display = PathBuf::from(display);
+    }
+    if let Cow::Owned(given) = rewriter.apply_uncounted(&s.path, &s.given.to_string_lossy()) {
+        s.given = PathBuf::from(given);
+    }
+}
+
+/// Report on `err` how many substitutions `--rewrite` made, over all
+/// sources so far.
+fn report_rewrites(options: &FormattingOptions, err: &mut dyn Write) -> io::Result<()> {
+    let Some(rewriter) = options.rewriter else {
+        return Ok(());
+    };
+    let counts: Vec<(&str, usize)> = rewriter.counts().filter(|(_, n)| *n > 0).collect();
+    if !counts.is_empty() {
+        let total: usize = counts.iter().map(|(_, n)| n).sum();
+        writeln!(err, "Rewrote {total} occurrence(s):")?;
+        for (pattern, n) in counts {
+            writeln!(err, "  {n} x {pattern}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Drop or truncate the lowest-priority sources so the bundle fits in
+/// `limit` tokens. Returns the surviving sources, in their original order,
+/// and a note describing what was left out.
+fn apply_budget(
+    sources: Vec<Source>,
+    limit: usize,
+    errors: &[FileError],
+    fmt: Option<&dyn Formatter>,
+    options: &FormattingOptions,
+) -> anyhow::Result<(Vec<Source>, Option<String>)> {
+    // Costs are measured on the rendered output, as `--split` does.
+    let collected = sources.len();
+    let tokens = |kept: &[Source], note: Option<&str>| -> anyhow::Result<usize> {
+        let extras = Extras::collect(kept, errors.len() + collected - kept.len(), options);
+        let mut buf = Vec::new();
+        let bundle = Bundle::new(kept, &extras, errors, note, options);
+        write_bundle(&bundle, fmt, options, &mut buf)?;
+        Ok(budget::estimate_tokens(&String::from_utf8_lossy(&buf)))
+    };
+    let base = tokens(&[], None)?;
+    if base >= limit {
+        anyhow::bail!(
+            "budget of {limit} tokens leaves no room for files after the tree, prompt and stats ({base} tokens)"
+        );
+    }
+    let must: Vec<PathBuf> = options.must.iter().map(|m| normalize(m)).collect();
+    let mut candidates = Vec::with_capacity(sources.len());
+    for s in &sources {
+        let empty = Source {
+            content: String::new(),
+            lines: None,
+            ..s.clone()
+        };
+        let path = normalize(&s.path);
+        candidates.push(Candidate {
+            content: &s.content,
+            rank: s.rank,
+            must: must.iter().any(|m| path.starts_with(m)),
+            cost: tokens(std::slice::from_ref(s), None)?.saturating_sub(base),
+            overhead: tokens(std::slice::from_ref(&empty), None)?.saturating_sub(base),
+        });
+    }
+
+    // The estimates do not add up exactly (the tree, stats and note grow
+    // with the files), so plan again with less room until the output fits.
+    let mut room = limit - base;
+    loop {
+        let verdicts = budget::plan(&candidates, room)?;
+        let (kept, note) = budget_verdicts(sources.clone(), verdicts, limit);
+        let total = tokens(&kept, note.as_deref())?;
+        if total <= limit || room == 0 {
+            return Ok((kept, note));
+        }
+        room = room.saturating_sub(total - limit);
+    }
+}
+
+/// `path` without `.` components, so `./src` and `src` compare equal.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|c| *c != std::path::Component::CurDir)
+        .collect()
+}
+
+/// The sources `verdicts` keep, truncated where they say so, and a note
+/// about what was left out.
+fn budget_verdicts(
+    sources: Vec<Source>,
+    verdicts: Vec<Verdict>,
+    limit: usize,
+) -> (Vec<Source>, Option<String>) {
+    let mut kept = Vec::with_capacity(sources.len());
+    let mut omitted = Vec::new();
+    let mut truncated = Vec::new();
+    for (mut s, verdict) in sources.into_iter().zip(verdicts) {
+        let display = s.display.display().to_string();
+        match verdict {
+            Verdict::Keep => kept.push(s),
+            Verdict::Truncate(keep) => {
+                s.content = budget::truncate(&s.content, keep);
+                truncated.push(display);
+                kept.push(s);
+            }
+            Verdict::Omit => omitted.push(display),
+        }
+    }
+
+    if omitted.is_empty() && truncated.is_empty() {
+        return (kept, None);
+    }
+    let mut note = vec![format!("budget of {limit} tokens reached")];
+    if !omitted.is_empty() {
+        note.push(format!(
+            "omitted {} file(s): {}",
+            omitted.len(),
+            omitted.join(", ")
+        ));
+    }
+    if !truncated.is_empty() {
+        note.push(format!("truncated: {}", truncated.join(", ")));
+    }
+    (kept, Some(note.join("; ")))
+}
+
+/// The prompt given by `--prompt-file`, or else by `--prompt` (a name from
+/// the prompt library or literal text).
+fn load_prompt(file: Option<&Path>, value: Option<String>) -> anyhow::Result<Prompt> {
+    Ok(match (file, value) {
+        (Some(file), _) => Prompt::load(file)?,
+        (None, Some(value)) => {
+            Prompt::resolve(&value, config_dir().map(|p| p.join("prompts")).as_deref())?
+        }
+        (None, None) => Prompt::default(),
+    })
+}
+
+/// Settings of `--format messages`, from the command line or the config.
+fn envelope(args: &Args, config: &Config) -> Envelope {
+    Envelope {
+        api: args
+            .messages_api
+            .or(config.messages_api)
+            .unwrap_or_default(),
+        content: args
+            .messages_content
+            .or(config.messages_content)
+            .unwrap_or_default(),
+        system: args.system.clone().or_else(|| config.system.clone()),
+        model: args.model.clone().or_else(|| config.model.clone()),
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     let mut args = match Args::parse_with_trailing() {
         Ok(args) => args,
@@ -202,107 +1152,312 @@ fn main() -> anyhow::Result<()> {
             std::process::exit(1);
         }
     };
+    highlight::set_dirs(config_dir(), dirs::cache_dir().map(|p| p.join("rucat")));
+    if print_info(&args) {
+        return Ok(());
+    }
     let config = load_config();
+    exit_on_error(check_output_flags(&args));
 
     // Handle clipboard provider if copy flag is set
     #[cfg(feature = "clipboard")]
-    let clipboard_provider = if args.copy {
-        args.clipboard_provider_for_test.as_ref().map_or_else(
-            || {
-                // Auto-detection would go here, but for tests we'll fail if no provider specified
-                eprintln!("Error: Failed to initialize clipboard");
-                std::process::exit(1);
-            },
-            |provider_name| match provider_name.as_str() {
-                "osc52" => Some(ClipboardProvider::Osc52),
-                "osc5522" => Some(ClipboardProvider::Osc5522),
-                _ => {
-                    eprintln!("Error: Invalid test provider '{provider_name}'");
-                    std::process::exit(1);
-                }
-            },
-        )
-    } else {
-        None
-    };
+    let clipboard_provider = select_clipboard_provider(&args);
 
     // Merge settings: CLI > Config File > Default
     let format = args
         .format
+        .or_else(|| args.template.as_ref().map(|_| OutputFormat::Template))
         .or(config.format)
         .unwrap_or(OutputFormat::Markdown);
-    let line_numbers = args.line_numbers || config.numbers.unwrap_or(false);
+    let formatter = formatter_options(&args, &config, format)?;
+    let messages = (format == OutputFormat::Messages).then(|| envelope(&args, &config));
     let strip = args.strip.or(config.strip).unwrap_or(0);
-    let ansi_width = args.ansi_width.or(config.ansi_width).unwrap_or(80);
-    let utf8_width = args.utf8_width.or(config.utf8_width).unwrap_or(80);
-    let pretty_syntax = args.pretty_syntax.or(config.pretty_syntax);
+    let tree = (args.tree || args.tree_info.is_some() || config.tree.unwrap_or(false))
+        .then(|| args.tree_info.or(config.tree_info).unwrap_or_default());
+    let secrets_mode = args.secrets.or(config.secrets).unwrap_or_default();
+    let allowlist = args
+        .secrets_allowlist
+        .or(config.secrets_allowlist)
+        .or_else(|| {
+            config_dir()
+                .map(|p| p.join("secrets-allowlist"))
+                .filter(|p| p.exists())
+        });
+    let scanner = build_scanner(secrets_mode, allowlist.as_deref())?;
+    let rewriter = build_rewriter(&args.rewrite, config.rewrite)?;
+    let prompt = load_prompt(args.prompt_file.as_deref(), args.prompt.or(config.prompt))?;
+    #[cfg(feature = "clipboard")]
+    let copying = args.copy;
+    #[cfg(not(feature = "clipboard"))]
+    let copying = false;
+    let budget = exit_on_error(
+        args.budget
+            .or(config.budget)
+            .map(|b| budget::parse_budget(&b))
+            .transpose(),
+    );
+    let split = exit_on_error(
+        args.split
+            .or(config.split)
+            .map(|s| SplitLimit::parse(&s))
+            .transpose(),
+    )
+    .map(|limit| Split {
+        limit,
+        dir: args.split_dir.as_deref(),
+        confirm_on_tty: args.files.is_empty() || args.null_sep,
+    });
 
     let formatting_options = FormattingOptions {
         format,
-        line_numbers,
+        formatter,
         strip,
-        pretty_syntax: pretty_syntax.as_deref(),
-        ansi_width,
-        utf8_width,
+        meta: if args.meta.is_empty() {
+            config.meta.unwrap_or_default()
+        } else {
+            args.meta
+        },
+        budget,
+        must: &args.must,
+        tree,
+        stats: args.stats.or(config.stats),
+        secrets: secrets_mode,
+        scanner: scanner.as_ref(),
+        copying,
+        rewriter: rewriter.as_ref(),
+        minify: Minify {
+            comments: args.strip_comments || config.strip_comments.unwrap_or(false),
+            doc_comments: args.strip_doc_comments || config.strip_doc_comments.unwrap_or(false),
+            whitespace: args.minify_whitespace || config.minify_whitespace.unwrap_or(false),
+        },
+        prompt,
+        split,
+        output_dir: args.output_dir.as_deref(),
+        messages,
     };
 
     // If the user passed -0/--null, pull a NUL-separated list of paths from stdin
     if args.null_sep {
-        let mut bytes = Vec::new();
-        io::stdin().read_to_end(&mut bytes)?;
-        for part in bytes.split(|b| *b == 0) {
-            if part.is_empty() {
-                continue;
-            }
-            #[cfg(unix)]
-            let pb = PathBuf::from(std::ffi::OsStr::from_bytes(part));
-            #[cfg(not(unix))]
-            let pb = PathBuf::from(String::from_utf8_lossy(part).to_string());
-            args.files.push(pb);
-        }
+        read_null_separated(&mut args.files)?;
     }
 
     // Collect all output in a buffer if copying to clipboard
-    #[cfg(feature = "clipboard")]
-    let mut clipboard_buffer = if args.copy { Some(Vec::new()) } else { None };
+    let to_stdout = args.output_dir.is_none() && (args.output.is_none() || args.tee);
+    let mut out = Output {
+        sink: to_stdout
+            .then(|| output_sink(args.paging.or(config.paging).unwrap_or_default(), copying)),
+        file: args.output.clone().map(|path| (path, Vec::new())),
+        #[cfg(feature = "clipboard")]
+        clipboard: clipboard_provider.map(|p| (p, Vec::new())),
+        reports: Vec::new(),
+    };
 
     // Process input
     if args.files.is_empty() && !args.null_sep {
-        process_stdin(
-            &formatting_options,
-            #[cfg(feature = "clipboard")]
-            &mut clipboard_buffer,
-        )?;
+        process_stdin(&formatting_options, &mut out)?;
     } else {
-        process_files(
-            &args.files,
-            &formatting_options,
-            #[cfg(feature = "clipboard")]
-            &mut clipboard_buffer,
-        )?;
+        process_files(&args.files, &formatting_options, &mut out)?;
     }
 
     // Write clipboard escape sequence if needed
+    out.copy_collected()?;
+
+    out.finish()?;
+    Ok(())
+}
+
+/// Reject `--output`, `--output-dir` and `--tee` in combinations that would
+/// leave one of them with nothing to do.
+fn check_output_flags(args: &Args) -> Result<(), String> {
+    if args.output.is_some() && args.output_dir.is_some() {
+        return Err("--output and --output-dir cannot be used together".to_owned());
+    }
+    if args.tee && args.output.is_none() {
+        return Err("--tee needs --output".to_owned());
+    }
+    if args.output_dir.is_some() && args.split.is_some() {
+        return Err("--output-dir cannot be used with --split".to_owned());
+    }
+    if args.output.is_some() && args.split_dir.is_some() {
+        return Err("--output cannot be used with --split-dir".to_owned());
+    }
     #[cfg(feature = "clipboard")]
-    if let Some(buffer) = clipboard_buffer
-        && let Some(provider) = clipboard_provider
-    {
-        let content = String::from_utf8_lossy(&buffer);
-        provider.copy_to_clipboard(&content, &mut io::stdout())?;
+    if args.copy && (args.output_dir.is_some() || args.split_dir.is_some()) {
+        return Err(
+            "--copy cannot be used with --output-dir or --split-dir, which write files instead"
+                .to_owned(),
+        );
     }
-
     Ok(())
 }
 
-fn read_file_content(p: &PathBuf) -> anyhow::Result<String> {
-    std::fs::read_to_string(p).map_err(anyhow::Error::from)
+/// Unwrap a flag value, or report the problem and exit like a parse error.
+fn exit_on_error<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    })
+}
+
+#[cfg(feature = "clipboard")]
+fn select_clipboard_provider(args: &Args) -> Option<ClipboardProvider> {
+    if args.copy {
+        args.clipboard_provider_for_test.as_ref().map_or_else(
+            || {
+                // Auto-detection would go here, but for tests we'll fail if no provider specified
+                eprintln!("Error: Failed to initialize clipboard");
+                std::process::exit(1);
+            },
+            |provider_name| match provider_name.as_str() {
+                "osc52" => Some(ClipboardProvider::Osc52),
+                "osc5522" => Some(ClipboardProvider::Osc5522),
+                _ => {
+                    eprintln!("Error: Invalid test provider '{provider_name}'");
+                    std::process::exit(1);
+                }
+            },
+        )
+    } else {
+        None
+    }
 }
 
-fn format_json(entries: &[FileEntry]) -> anyhow::Result<()> {
-    writeln!(io::stdout(), "{}", serde_json::to_string_pretty(entries)?)?;
+/// Append the NUL-separated paths read from stdin to `files`.
+fn read_null_separated(files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    io::stdin().read_to_end(&mut bytes)?;
+    for part in bytes.split(|b| *b == 0) {
+        if part.is_empty() {
+            continue;
+        }
+        #[cfg(unix)]
+        let pb = PathBuf::from(std::ffi::OsStr::from_bytes(part));
+        #[cfg(not(unix))]
+        let pb = PathBuf::from(String::from_utf8_lossy(part).to_string());
+        files.push(pb);
+    }
     Ok(())
 }
 
+fn build_scanner(mode: SecretsMode, allowlist: Option<&Path>) -> anyhow::Result<Option<Scanner>> {
+    if mode == SecretsMode::Off {
+        return Ok(None);
+    }
+    let allow = match allowlist {
+        Some(path) => Scanner::load_allowlist(path)?,
+        None => Vec::new(),
+    };
+    Ok(Some(Scanner::new(allow)))
+}
+
+/// Combine the config file's `[[rewrite]]` rules with `--rewrite` flags,
+/// which run after them.
+fn build_rewriter(
+    flags: &[String],
+    mut rules: Vec<RewriteRule>,
+) -> anyhow::Result<Option<Rewriter>> {
+    for flag in flags {
+        rules.push(RewriteRule::parse(flag).map_err(anyhow::Error::msg)?);
+    }
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Rewriter::new(&rules)?))
+}
+
+/// Merge the settings for the formatter itself: CLI > config > default.
+fn formatter_options(
+    args: &Args,
+    config: &Config,
+    format: OutputFormat,
+) -> anyhow::Result<FormatterOptions> {
+    Ok(FormatterOptions {
+        line_numbers: args.line_numbers || config.numbers.unwrap_or(false),
+        ansi_width: args
+            .ansi_width
+            .or(config.ansi_width)
+            .unwrap_or_else(box_width),
+        ansi_style: config.ansi_style.unwrap_or_default(),
+        ansi_highlight: args.ansi_highlight || config.ansi_highlight.unwrap_or(false),
+        utf8_width: args
+            .utf8_width
+            .or(config.utf8_width)
+            .unwrap_or_else(box_width),
+        tabs: args.tabs.or(config.tabs).unwrap_or(4),
+        wrap: args.wrap.or(config.wrap).unwrap_or_default(),
+        pretty_syntax: args
+            .pretty_syntax
+            .clone()
+            .or_else(|| config.pretty_syntax.clone()),
+        theme: matches!(
+            format,
+            OutputFormat::Pretty | OutputFormat::Html | OutputFormat::Ansi
+        )
+        .then(|| {
+            highlight::resolve_theme(
+                args.theme.as_deref().or(config.theme.as_deref()),
+                config.theme_light.as_deref(),
+                config.theme_dark.as_deref(),
+            )
+        })
+        .transpose()?,
+        // Parts written to files are never shown on this terminal.
+        color: ColorLevel::detect(
+            args.color.or(config.color).unwrap_or_default(),
+            args.split_dir.is_none()
+                && args.output_dir.is_none()
+                && (args.output.is_none() || args.tee)
+                && io::stdout().is_terminal(),
+        ),
+        decorations: args.decorations.or(config.decorations).unwrap_or_default(),
+        markdown_fence: args
+            .markdown_fence
+            .or(config.markdown_fence)
+            .unwrap_or_default(),
+        markdown_header: args
+            .markdown_header
+            .or(config.markdown_header)
+            .unwrap_or_default(),
+        xml_cdata: args.xml_cdata || config.xml_cdata.unwrap_or(false),
+        html_style: args.html_style.or(config.html_style).unwrap_or_default(),
+        template: load_template(format, args.template.as_deref(), config.template.as_deref())?,
+        documents_metadata: args.documents_metadata || config.documents_metadata.unwrap_or(false),
+    })
+}
+
+/// Interior width of the `ansi` and `utf8` boxes: the terminal width less
+/// the two borders, or 80 columns when not writing to a terminal.
+fn box_width() -> usize {
+    terminal_size::terminal_size().map_or(80, |(terminal_size::Width(w), _)| {
+        usize::from(w).saturating_sub(2)
+    })
+}
+
+/// The template for `--format template`, from `--template FILE` or else the
+/// config file's `template` text.
+fn load_template(
+    format: OutputFormat,
+    file: Option<&Path>,
+    inline: Option<&str>,
+) -> anyhow::Result<Option<Template>> {
+    if format != OutputFormat::Template {
+        return Ok(None);
+    }
+    let text = match (file, inline) {
+        (Some(path), _) => fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read template {}: {e}", path.display()))?,
+        (None, Some(text)) => text.to_owned(),
+        (None, None) => anyhow::bail!(
+            "the template format needs --template FILE or a `template` in config.toml"
+        ),
+    };
+    Ok(Some(Template::parse(&text).map_err(anyhow::Error::msg)?))
+}
+
+fn read_file_content(p: &PathBuf) -> anyhow::Result<String> {
+    std::fs::read_to_string(p).map_err(anyhow::Error::from)
+}
+
 fn strip_components(p: &Path, n: usize) -> PathBuf {
     let parts: Vec<_> = p.iter().collect();
     if parts.is_empty() {
@@ -0,0 +1,116 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::cli::{MessagesApi, MessagesContent};
+use serde::Serialize;
+
+/// `max_tokens` sent to the Anthropic API, which requires one.
+pub const DEFAULT_MAX_TOKENS: usize = 4096;
+
+/// Settings of `--format messages`.
+#[derive(Debug, Clone, Default)]
+pub struct Envelope {
+    pub api: MessagesApi,
+    pub content: MessagesContent,
+    pub system: Option<String>,
+    pub model: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    messages: Vec<Message<'a>>,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'static str,
+    content: Content<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Content<'a> {
+    Text(&'a str),
+    Blocks(Vec<Block<'a>>),
+}
+
+#[derive(Serialize)]
+struct Block<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    text: &'a str,
+}
+
+impl Envelope {
+    /// A request body whose user message holds `blocks` (the prompt, each
+    /// file and any notes, in order), as separate content blocks or joined
+    /// into one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request cannot be serialised.
+    pub fn to_json(&self, blocks: &[String]) -> serde_json::Result<String> {
+        let joined;
+        let user = match self.content {
+            MessagesContent::Files => Content::Blocks(
+                blocks
+                    .iter()
+                    .map(|text| Block { kind: "text", text })
+                    .collect(),
+            ),
+            MessagesContent::Single => {
+                joined = blocks.join("\n");
+                Content::Blocks(vec![Block {
+                    kind: "text",
+                    text: &joined,
+                }])
+            }
+        };
+        let mut messages = Vec::with_capacity(2);
+        let system = self.system.as_deref();
+        if let (MessagesApi::Openai, Some(system)) = (self.api, system) {
+            messages.push(Message {
+                role: "system",
+                content: Content::Text(system),
+            });
+        }
+        messages.push(Message {
+            role: "user",
+            content: user,
+        });
+        let request = match self.api {
+            MessagesApi::Openai => Request {
+                model: self.model.as_deref(),
+                max_tokens: None,
+                system: None,
+                messages,
+            },
+            MessagesApi::Anthropic => Request {
+                model: self.model.as_deref(),
+                max_tokens: Some(DEFAULT_MAX_TOKENS),
+                system,
+                messages,
+            },
+        };
+        serde_json::to_string_pretty(&request)
+    }
+}
@@ -0,0 +1,143 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::cli::MetaField;
+use crate::language::language_name;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// The `--meta` fields of one file. Fields that were not requested, or that
+/// do not apply (e.g. `mtime` of stdin), are `None`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize)]
+pub struct Metadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Modification time in RFC 3339 format (UTC, whole seconds).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Lowercase hex digest of the file as read.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Permission bits in octal, e.g. `0644` (Unix only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+}
+
+impl Metadata {
+    /// Gather `fields` for the file at `path` whose contents are `content`.
+    /// Without `stat` (e.g. for stdin), the fields that need the file system
+    /// are left out.
+    #[must_use]
+    pub fn collect(
+        fields: &[MetaField],
+        path: &Path,
+        content: &str,
+        stat: Option<&fs::Metadata>,
+    ) -> Self {
+        let mut meta = Self::default();
+        for field in fields {
+            match field {
+                MetaField::Size => meta.size = Some(content.len() as u64),
+                MetaField::Mtime => {
+                    meta.mtime = stat
+                        .and_then(|m| m.modified().ok())
+                        .map(|t| humantime::format_rfc3339_seconds(t).to_string());
+                }
+                MetaField::Lines => meta.lines = Some(content.lines().count()),
+                MetaField::Lang => meta.lang = Some(language_name(path, content).to_owned()),
+                MetaField::Sha256 => {
+                    meta.sha256 = Some(Sha256::digest(content).iter().fold(
+                        String::with_capacity(64),
+                        |mut hex, b| {
+                            let _ = write!(hex, "{b:02x}");
+                            hex
+                        },
+                    ));
+                }
+                MetaField::Mode => meta.mode = stat.and_then(mode),
+            }
+        }
+        meta
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The fields that are set, as `(name, value)` pairs for formats that
+    /// show them as attributes. They come in the order of the struct, as in
+    /// JSON, whatever order `--meta` lists them in.
+    #[must_use]
+    pub fn attributes(&self) -> Vec<(&'static str, String)> {
+        let mut attrs = Vec::new();
+        attrs.extend(self.size.map(|n| ("size", n.to_string())));
+        attrs.extend(self.mtime.clone().map(|t| ("mtime", t)));
+        attrs.extend(self.lines.map(|n| ("lines", n.to_string())));
+        attrs.extend(self.lang.clone().map(|l| ("lang", l)));
+        attrs.extend(self.sha256.clone().map(|h| ("sha256", h)));
+        attrs.extend(self.mode.clone().map(|m| ("mode", m)));
+        attrs
+    }
+
+    /// A short description for file headers, e.g. `319 lines, Rust`; empty
+    /// when no field is set.
+    #[must_use]
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        parts.extend(self.size.map(|n| format!("{n} bytes")));
+        parts.extend(self.mtime.as_ref().map(|t| format!("modified {t}")));
+        parts.extend(
+            self.lines
+                .map(|n| format!("{n} line{}", if n == 1 { "" } else { "s" })),
+        );
+        parts.extend(self.lang.clone());
+        parts.extend(self.sha256.as_ref().map(|h| format!("sha256 {h}")));
+        parts.extend(self.mode.as_ref().map(|m| format!("mode {m}")));
+        parts.join(", ")
+    }
+
+    /// `path` followed by the summary in parentheses, if there is one.
+    #[must_use]
+    pub fn label(&self, path: &Path) -> String {
+        let summary = self.summary();
+        if summary.is_empty() {
+            path.display().to_string()
+        } else {
+            format!("{} ({summary})", path.display())
+        }
+    }
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)] // Must match signature of other cfg branch
+fn mode(stat: &fs::Metadata) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(format!("{:04o}", stat.permissions().mode() & 0o7777))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+const fn mode(_stat: &fs::Metadata) -> Option<String> {
+    None
+}
@@ -0,0 +1,219 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::highlight::SYNTAX_SET;
+use crate::language::Language;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference};
+use syntect::util::LinesWithEndings;
+
+static COMMENT: LazyLock<Scope> = LazyLock::new(|| Scope::new("comment").unwrap());
+static LINE_DOC: LazyLock<Scope> =
+    LazyLock::new(|| Scope::new("comment.line.documentation").unwrap());
+static BLOCK_DOC: LazyLock<Scope> =
+    LazyLock::new(|| Scope::new("comment.block.documentation").unwrap());
+
+/// File contents with some lines removed, remembering where each remaining
+/// line came from so `-n` can still show the original line numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Minified {
+    pub content: String,
+    /// 1-based line number in the original file of each line of `content`.
+    pub lines: Vec<usize>,
+}
+
+impl Minified {
+    /// Wrap unmodified content.
+    #[must_use]
+    pub fn new(content: &str) -> Self {
+        Self {
+            content: content.to_owned(),
+            lines: (1..=content.lines().count()).collect(),
+        }
+    }
+
+    fn from_lines(lines: Vec<(usize, String)>) -> Self {
+        let mut content = String::new();
+        let mut numbers = Vec::with_capacity(lines.len());
+        for (number, line) in lines {
+            content.push_str(&line);
+            content.push('\n');
+            numbers.push(number);
+        }
+        Self {
+            content,
+            lines: numbers,
+        }
+    }
+
+    /// Remove line and block comments, using the scopes of the syntax
+    /// definition for `path` so comment markers inside string literals are
+    /// left alone. Doc comments are kept unless `keep_docs` is false, but a
+    /// leading copyright or license header is always removed and a shebang
+    /// line is always kept. Lines that held nothing but a comment disappear
+    /// entirely. Files in a language syntect does not know are returned
+    /// unchanged.
+    #[must_use]
+    pub fn strip_comments(self, path: &Path, keep_docs: bool) -> Self {
+        let Some(syntax) = syntax_for(path, &self.content) else {
+            return self;
+        };
+        let Some(classified) = classify(syntax, &self.content) else {
+            return self;
+        };
+
+        let header = license_header(&classified);
+        let lines = classified
+            .into_iter()
+            .zip(self.lines)
+            .enumerate()
+            .filter_map(|(idx, (line, number))| {
+                if header.contains(&idx) {
+                    return None;
+                }
+                if idx == 0 && line.raw.starts_with("#!") {
+                    return Some((number, line.raw));
+                }
+                let kept = if keep_docs {
+                    line.without_comments
+                } else {
+                    line.without_any_comments
+                };
+                let kept = kept.trim_end();
+                if kept.trim().is_empty() && !line.raw.trim().is_empty() {
+                    None
+                } else {
+                    Some((number, kept.to_owned()))
+                }
+            })
+            .collect();
+        Self::from_lines(lines)
+    }
+
+    /// Trim trailing whitespace and collapse runs of blank lines into one,
+    /// dropping blank lines at the start and end entirely.
+    #[must_use]
+    pub fn minify_whitespace(self) -> Self {
+        let mut out: Vec<(usize, String)> = Vec::new();
+        let mut previous_blank = true;
+        for (line, number) in self.content.lines().zip(self.lines) {
+            let line = line.trim_end();
+            let blank = line.is_empty();
+            if !(blank && previous_blank) {
+                out.push((number, line.to_owned()));
+            }
+            previous_blank = blank;
+        }
+        if out.last().is_some_and(|(_, l)| l.is_empty()) {
+            out.pop();
+        }
+        Self::from_lines(out)
+    }
+}
+
+/// One line split by comment scopes.
+struct Classified {
+    raw: String,
+    /// The line with ordinary (non-doc) comments removed.
+    without_comments: String,
+    /// The line with every comment removed.
+    without_any_comments: String,
+    /// The line consists only of comments (of any kind) and whitespace.
+    all_comment: bool,
+}
+
+fn syntax_for(path: &Path, content: &str) -> Option<&'static SyntaxReference> {
+    Language::detect(path, content, None)
+        .and_then(Language::syntax)
+        .or_else(|| SYNTAX_SET.find_syntax_by_first_line(content))
+        .filter(|s| s.name != "Plain Text")
+}
+
+fn classify(syntax: &SyntaxReference, content: &str) -> Option<Vec<Classified>> {
+    let mut state = ParseState::new(syntax);
+    let mut stack = ScopeStack::new();
+    let mut out = Vec::new();
+    for line in LinesWithEndings::from(content) {
+        let ops = state.parse_line(line, &SYNTAX_SET).ok()?;
+        let text = line.trim_end_matches(['\n', '\r']);
+        let mut cl = Classified {
+            raw: text.to_owned(),
+            without_comments: String::new(),
+            without_any_comments: String::new(),
+            all_comment: true,
+        };
+        let mut pos = 0;
+        let mut push = |cl: &mut Classified, stack: &ScopeStack, end: usize| {
+            let end = end.min(text.len());
+            if pos >= end {
+                return;
+            }
+            let segment = &text[pos..end];
+            let (comment, doc) = comment_kind(stack);
+            if !comment {
+                cl.without_comments.push_str(segment);
+                cl.without_any_comments.push_str(segment);
+                if !segment.trim().is_empty() {
+                    cl.all_comment = false;
+                }
+            } else if doc {
+                cl.without_comments.push_str(segment);
+            }
+            pos = end;
+        };
+        for (at, op) in ops {
+            push(&mut cl, &stack, at);
+            stack.apply(&op).ok()?;
+        }
+        push(&mut cl, &stack, text.len());
+        out.push(cl);
+    }
+    Some(out)
+}
+
+/// Whether the innermost scopes are a comment, and whether it is a doc comment.
+fn comment_kind(stack: &ScopeStack) -> (bool, bool) {
+    let scopes = stack.as_slice();
+    let comment = scopes.iter().any(|s| COMMENT.is_prefix_of(*s));
+    let doc = scopes
+        .iter()
+        .any(|s| LINE_DOC.is_prefix_of(*s) || BLOCK_DOC.is_prefix_of(*s));
+    (comment, doc)
+}
+
+/// Lines that form a leading copyright or license comment, ending at the
+/// first blank line (a shebang line in front of it is kept).
+fn license_header(lines: &[Classified]) -> Range<usize> {
+    let start = usize::from(lines.first().is_some_and(|l| l.raw.starts_with("#!")));
+    let end = lines[start..]
+        .iter()
+        .position(|l| !l.all_comment || l.raw.trim().is_empty())
+        .map_or(lines.len(), |p| p + start);
+    let text = lines[start..end]
+        .iter()
+        .map(|l| l.raw.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+    if text.contains("copyright") || text.contains("license") {
+        // Take the blank line separating the header from the code with it.
+        let blank = lines.get(end).is_some_and(|l| l.raw.trim().is_empty());
+        start..end + usize::from(blank)
+    } else {
+        0..0
+    }
+}
@@ -0,0 +1,184 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! Writing output to files with `--output` and `--output-dir`.
+
+use crate::json::FileError;
+use crate::stats::Stats;
+use crate::tree::Tree;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+/// Name of the index `--output-dir` writes next to the files.
+pub const INDEX: &str = "index.md";
+
+/// Replace `path` with `contents` in one step: they are written to a
+/// temporary file beside it, which is then renamed over `path`. Readers see
+/// the old file or the new one, never a partial write. A file that is
+/// replaced keeps its permissions.
+///
+/// # Errors
+///
+/// Returns an error if the temporary file cannot be written or renamed.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file name"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp = dir.join(temp_name);
+
+    let written = fs::File::create(&temp).and_then(|mut file| {
+        file.write_all(contents)?;
+        if let Ok(old) = fs::metadata(path) {
+            file.set_permissions(old.permissions())?;
+        }
+        file.sync_all()
+    });
+    let result = written.and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Paths of the files `--output-dir` writes, relative to the directory.
+/// Each mirrors the path shown for its source, so `src/lib.rs` in Markdown
+/// becomes `src/lib.rs.md`.
+#[derive(Debug, Default)]
+pub struct Mirror {
+    taken: HashSet<PathBuf>,
+}
+
+impl Mirror {
+    /// Paths for a directory that also holds the [`INDEX`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            taken: HashSet::from([PathBuf::from(INDEX)]),
+        }
+    }
+
+    /// Where the output for `display` goes, with `extension` appended.
+    /// Components that would leave the directory (`..`, a root or a drive)
+    /// are dropped, stdin (`-`) becomes `stdin`, and a path that is already
+    /// taken gets a number: `a.rs.2.md`.
+    pub fn path(&mut self, display: &Path, extension: &str) -> PathBuf {
+        let mut base: PathBuf = display
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part),
+                _ => None,
+            })
+            .collect();
+        if base.as_os_str().is_empty() || base == Path::new("-") {
+            base = PathBuf::from("stdin");
+        }
+        let with = |suffix: &str| {
+            let mut name = base.clone().into_os_string();
+            name.push(suffix);
+            PathBuf::from(name)
+        };
+        let mut path = with(&format!(".{extension}"));
+        let mut n = 2;
+        while self.taken.contains(&path) {
+            path = with(&format!(".{n}.{extension}"));
+            n += 1;
+        }
+        self.taken.insert(path.clone());
+        path
+    }
+}
+
+/// The Markdown index `--output-dir` writes: the files, each linked to its
+/// output, and whatever the single-file output carries besides them.
+#[derive(Debug, Default)]
+pub struct Index<'a> {
+    pub prompt: Option<&'a str>,
+    pub tree: Option<&'a Tree>,
+    /// Each source's shown path and where its output was written.
+    pub files: Vec<(&'a Path, PathBuf)>,
+    pub errors: &'a [FileError],
+    pub stats: Option<&'a Stats>,
+    pub note: Option<&'a str>,
+    pub prompt_after: Option<&'a str>,
+}
+
+impl Index<'_> {
+    #[must_use]
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# Index\n");
+        let mut block = |text: &str| {
+            out.push('\n');
+            out.push_str(text.trim_end_matches('\n'));
+            out.push('\n');
+        };
+        if let Some(text) = self.prompt {
+            block(text);
+        }
+        if let Some(tree) = self.tree {
+            block(&format!("```text\n{}\n```", tree.lines(true).join("\n")));
+        }
+        let mut list = String::new();
+        for (shown, written) in &self.files {
+            let _ = writeln!(list, "- [{}]({})", shown.display(), link(written));
+        }
+        if !list.is_empty() {
+            block(&list);
+        }
+        if !self.errors.is_empty() {
+            let mut errors = String::from("## Errors\n\n");
+            for e in self.errors {
+                let _ = writeln!(errors, "- {}: {}", e.path, e.error);
+            }
+            block(&errors);
+        }
+        if let Some(stats) = self.stats {
+            block(&format!("```text\n{}\n```", stats.lines().join("\n")));
+        }
+        if let Some(note) = self.note {
+            block(&format!("> {note}"));
+        }
+        if let Some(text) = self.prompt_after {
+            block(text);
+        }
+        out
+    }
+}
+
+/// `path` as a Markdown link target: with `/` separators, and in angle
+/// brackets when it has characters that would end the link.
+fn link(path: &Path) -> String {
+    let target = path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    if target.contains([' ', '(', ')']) {
+        format!("<{target}>")
+    } else {
+        target
+    }
+}
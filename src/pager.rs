@@ -0,0 +1,197 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! Paging long output through `$RUCAT_PAGER`, `$PAGER` or `less -R`.
+
+use std::io::{self, Write};
+use std::mem;
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// The pager to run: `$RUCAT_PAGER`, then `$PAGER`, then `less -R`, split
+/// into the program and its arguments. A plain `less` gets `-R` so colours
+/// survive. `None` when the variable is set but empty.
+pub fn command(var: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    let line = var("RUCAT_PAGER")
+        .or_else(|| var("PAGER"))
+        .unwrap_or_else(|| "less".to_owned());
+    let mut words: Vec<String> = line.split_whitespace().map(str::to_owned).collect();
+    if words.len() == 1 && words[0].rsplit('/').next() == Some("less") {
+        words.push("-R".to_owned());
+    }
+    (!words.is_empty()).then_some(words)
+}
+
+/// Output that goes to `inner` until it grows past `height` lines, and from
+/// then on through a pager. Call [`Paged::finish`] to write what is still
+/// held back and wait for the pager to exit.
+pub struct Paged<W: Write> {
+    inner: W,
+    state: State,
+}
+
+enum State {
+    /// Holding output back until it is known whether it fits the screen.
+    Holding {
+        command: Vec<String>,
+        height: usize,
+        held: Vec<u8>,
+        lines: usize,
+    },
+    /// Writing through the pager.
+    Paging(Child, ChildStdin),
+    /// Writing straight to `inner`.
+    Direct,
+    /// The pager quit early; output is dropped.
+    Closed,
+}
+
+impl<W: Write> Paged<W> {
+    /// Output that pages with `command` once it is longer than `height`
+    /// lines, or at once with `height` 0.
+    pub const fn new(inner: W, command: Vec<String>, height: usize) -> Self {
+        Self {
+            inner,
+            state: State::Holding {
+                command,
+                height,
+                held: Vec::new(),
+                lines: 0,
+            },
+        }
+    }
+
+    /// Output that never pages.
+    pub const fn direct(inner: W) -> Self {
+        Self {
+            inner,
+            state: State::Direct,
+        }
+    }
+
+    /// Whether output is going through the pager.
+    pub const fn is_paging(&self) -> bool {
+        matches!(self.state, State::Paging(..))
+    }
+
+    /// Write what is held back, or close the pager's input and wait for
+    /// the reader to quit it.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if writing or waiting for the pager fails.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.close()
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        match mem::replace(&mut self.state, State::Closed) {
+            State::Holding { held, .. } => {
+                self.inner.write_all(&held)?;
+                self.inner.flush()
+            }
+            State::Paging(mut child, stdin) => {
+                drop(stdin);
+                child.wait().map(drop)
+            }
+            State::Direct => self.inner.flush(),
+            State::Closed => Ok(()),
+        }
+    }
+
+    /// Start the pager and hand it what was held back. If it cannot be
+    /// started, output goes to `inner` instead.
+    fn start(&mut self, command: &[String], held: &[u8]) -> io::Result<()> {
+        let spawned = Command::new(&command[0])
+            .args(&command[1..])
+            .stdin(Stdio::piped())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                let Some(stdin) = child.stdin.take() else {
+                    return Err(io::Error::other("pager has no input"));
+                };
+                self.state = State::Paging(child, stdin);
+            }
+            Err(e) => {
+                eprintln!("Warning: cannot run pager '{}': {e}", command.join(" "));
+                self.state = State::Direct;
+            }
+        }
+        self.write_through(held)
+    }
+
+    /// Write `buf` where output currently goes.
+    fn write_through(&mut self, buf: &[u8]) -> io::Result<()> {
+        let result = match &mut self.state {
+            State::Paging(_, stdin) => stdin.write_all(buf),
+            State::Direct => self.inner.write_all(buf),
+            State::Holding { .. } | State::Closed => Ok(()),
+        };
+        match result {
+            // The reader quit the pager: nothing more needs showing.
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe && self.is_paging() => {
+                self.state = State::Closed;
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+impl<W: Write> Write for Paged<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let State::Holding {
+            height,
+            held,
+            lines,
+            ..
+        } = &mut self.state
+        {
+            held.extend_from_slice(buf);
+            *lines += buf.iter().filter(|&&b| b == b'\n').count();
+            if *lines >= *height
+                && let State::Holding { command, held, .. } =
+                    mem::replace(&mut self.state, State::Closed)
+            {
+                self.start(&command, &held)?;
+            }
+        } else {
+            self.write_through(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.state {
+            State::Paging(_, stdin) => stdin.flush().or_else(|e| {
+                if e.kind() == io::ErrorKind::BrokenPipe {
+                    Ok(())
+                } else {
+                    Err(e)
+                }
+            }),
+            State::Direct => self.inner.flush(),
+            State::Holding { .. } | State::Closed => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for Paged<W> {
+    fn drop(&mut self) {
+        // Leave the terminal to the pager until the reader is done.
+        let _ = self.close();
+    }
+}
@@ -0,0 +1,102 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use std::path::{Path, PathBuf};
+
+/// Line that separates the preamble from the postamble in a prompt.
+pub const FILES_MARKER: &str = "{{files}}";
+
+/// Extensions tried, in order, when looking up a named prompt.
+const EXTENSIONS: [&str; 3] = ["", "md", "txt"];
+
+/// Text placed around the bundle by `--prompt` or `--prompt-file`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Prompt {
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Prompt {
+    /// Split prompt text at a line holding only `{{files}}`: what comes
+    /// before it precedes the bundle and what comes after follows it. Text
+    /// without the marker is all preamble.
+    #[must_use]
+    pub fn parse(text: &str) -> Self {
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        let mut seen = false;
+        for line in text.lines() {
+            if !seen && line.trim() == FILES_MARKER {
+                seen = true;
+            } else if seen {
+                after.push(line);
+            } else {
+                before.push(line);
+            }
+        }
+        let join = |lines: Vec<&str>| {
+            let text = lines.join("\n");
+            let text = text.trim();
+            (!text.is_empty()).then(|| text.to_owned())
+        };
+        Self {
+            before: join(before),
+            after: join(after),
+        }
+    }
+
+    /// Resolve the value of `--prompt`: the name of a prompt in `library`
+    /// (`review` finds `review`, `review.md` or `review.txt`), or otherwise
+    /// the prompt text itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a matching library file cannot be read.
+    pub fn resolve(value: &str, library: Option<&Path>) -> anyhow::Result<Self> {
+        library
+            .and_then(|dir| find(dir, value))
+            .map_or_else(|| Ok(Self::parse(value)), |path| Self::load(&path))
+    }
+
+    /// Read a prompt from a file (`--prompt-file`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read prompt {}: {e}", path.display()))?;
+        Ok(Self::parse(&text))
+    }
+}
+
+/// The library file for prompt `name`, if there is one. Names containing a
+/// path separator are never looked up, so literal text is left alone.
+fn find(dir: &Path, name: &str) -> Option<PathBuf> {
+    if name.is_empty() || name.contains(['/', '\\', '\n']) || name.starts_with('.') {
+        return None;
+    }
+    EXTENSIONS
+        .iter()
+        .map(|ext| {
+            if ext.is_empty() {
+                dir.join(name)
+            } else {
+                dir.join(format!("{name}.{ext}"))
+            }
+        })
+        .find(|p| p.is_file())
+}
@@ -0,0 +1,180 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::path::Path;
+
+/// A `[[rewrite]]` rule from the config file or a `--rewrite` flag.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    /// Regular expression to search for.
+    pub pattern: String,
+    /// Replacement text; `$1`, `${name}` refer to capture groups.
+    pub replacement: String,
+    /// Only rewrite files whose path matches this glob.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+impl RewriteRule {
+    /// Parse the sed-style `s/PATTERN/REPLACEMENT/` syntax of `--rewrite`.
+    /// Any character may follow the `s` as delimiter (`s|/home/[^/]+|~|`),
+    /// and `\` before the delimiter makes it literal (`s/\/home\/me/~/`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the value is not in that form.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid rewrite '{s}', expected s/PATTERN/REPLACEMENT/");
+        let rest = s.strip_prefix('s').ok_or_else(invalid)?;
+        let delim = rest.chars().next().ok_or_else(invalid)?;
+        let parts = split_escaped(&rest[delim.len_utf8()..], delim);
+        match parts
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .as_slice()
+        {
+            [pattern, replacement, ""] | [pattern, replacement] if !pattern.is_empty() => {
+                Ok(Self {
+                    pattern: pattern.replace(ESCAPED, &regex::escape(&delim.to_string())),
+                    replacement: replacement.replace(
+                        ESCAPED,
+                        if delim == '$' {
+                            "$$"
+                        } else {
+                            &rest[..delim.len_utf8()]
+                        },
+                    ),
+                    path: None,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Stands in for an escaped delimiter until the parts have been split.
+const ESCAPED: char = '\u{0}';
+
+/// Split `s` at each `delim` that is not preceded by `\`, replacing the
+/// escaped ones with [`ESCAPED`]. Other escapes (`\d`) are left alone.
+fn split_escaped(s: &str, delim: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().expect("parts is never empty");
+        match c {
+            '\\' => match chars.next() {
+                Some(next) if next == delim => part.push(ESCAPED),
+                Some(next) => {
+                    part.push(c);
+                    part.push(next);
+                }
+                None => part.push(c),
+            },
+            c if c == delim => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+    parts
+}
+
+struct Compiled {
+    re: Regex,
+    replacement: String,
+    glob: Option<GlobMatcher>,
+}
+
+/// Applies rewrite rules to file bodies and display paths, counting how
+/// many substitutions each rule made.
+pub struct Rewriter {
+    rules: Vec<Compiled>,
+    counts: Vec<Cell<usize>>,
+}
+
+impl Rewriter {
+    /// Compile `rules`, in the order they will be applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern or path glob is invalid.
+    pub fn new(rules: &[RewriteRule]) -> anyhow::Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|r| {
+                let re = Regex::new(&r.pattern)
+                    .map_err(|e| anyhow::anyhow!("invalid rewrite pattern '{}': {e}", r.pattern))?;
+                let glob = r
+                    .path
+                    .as_deref()
+                    .map(|g| Glob::new(g).map(|g| g.compile_matcher()))
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("invalid rewrite path glob: {e}"))?;
+                Ok(Compiled {
+                    re,
+                    replacement: r.replacement.clone(),
+                    glob,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let counts = rules.iter().map(|_| Cell::new(0)).collect();
+        Ok(Self { rules, counts })
+    }
+
+    /// Rewrite `text` (a body or display path) of the file at `path`.
+    #[must_use]
+    pub fn apply<'t>(&self, path: &Path, text: &'t str) -> Cow<'t, str> {
+        self.rewrite(path, text, true)
+    }
+
+    /// Like [`Rewriter::apply`], but the substitutions are not counted: for
+    /// another copy of text that has been counted already, such as the path
+    /// before `--strip`.
+    #[must_use]
+    pub fn apply_uncounted<'t>(&self, path: &Path, text: &'t str) -> Cow<'t, str> {
+        self.rewrite(path, text, false)
+    }
+
+    fn rewrite<'t>(&self, path: &Path, text: &'t str, counted: bool) -> Cow<'t, str> {
+        let mut out = Cow::Borrowed(text);
+        for (rule, count) in self.rules.iter().zip(&self.counts) {
+            if rule.glob.as_ref().is_some_and(|g| !g.is_match(path)) {
+                continue;
+            }
+            let hits = rule.re.find_iter(&out).count();
+            if hits > 0 {
+                if counted {
+                    count.set(count.get() + hits);
+                }
+                out = Cow::Owned(rule.re.replace_all(&out, &rule.replacement).into_owned());
+            }
+        }
+        out
+    }
+
+    /// Substitutions made so far, as `(pattern, count)` in rule order.
+    pub fn counts(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.rules
+            .iter()
+            .zip(&self.counts)
+            .map(|(r, c)| (r.re.as_str(), c.get()))
+    }
+}
@@ -0,0 +1,213 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// Shannon entropy (bits per byte) a quoted string must exceed to be
+/// reported as `high-entropy`.
+const ENTROPY_THRESHOLD: f64 = 4.5;
+
+/// Entropy a value assigned to a secret-looking name (`password = ...`) must
+/// exceed; lower than [`ENTROPY_THRESHOLD`] because the name is evidence too.
+const ASSIGNMENT_ENTROPY_THRESHOLD: f64 = 3.0;
+
+struct Rule {
+    kind: &'static str,
+    re: Regex,
+    /// Minimum entropy of the matched secret, for the heuristic rules.
+    min_entropy: Option<f64>,
+}
+
+impl Rule {
+    fn new(kind: &'static str, re: &str) -> Self {
+        Self {
+            kind,
+            re: Regex::new(re).unwrap(),
+            min_entropy: None,
+        }
+    }
+
+    fn entropy(kind: &'static str, re: &str, min: f64) -> Self {
+        Self {
+            min_entropy: Some(min),
+            ..Self::new(kind, re)
+        }
+    }
+}
+
+// Rules are tried in order; when two matches overlap the earlier rule wins,
+// so specific patterns come before the generic ones. If a rule has a capture
+// group, only the first group that matched is redacted.
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(|| {
+    vec![
+        Rule::new(
+            "private-key",
+            r"(?s)-----BEGIN [A-Z0-9 ]*PRIVATE KEY(?: BLOCK)?-----.*?-----END [A-Z0-9 ]*PRIVATE KEY(?: BLOCK)?-----",
+        ),
+        Rule::new("aws-key", r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b"),
+        Rule::new(
+            "aws-secret",
+            r#"(?i)aws_?secret_?access_?key["']?\s*[:=]\s*["']?([A-Za-z0-9/+=]{40})"#,
+        ),
+        Rule::new("gcp-key", r"\bAIza[0-9A-Za-z_\-]{35}"),
+        Rule::new(
+            "github-token",
+            r"\b(?:gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,})",
+        ),
+        Rule::new("slack-token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+        Rule::new("stripe-key", r"\b[rs]k_live_[0-9A-Za-z]{24,}"),
+        Rule::new("anthropic-key", r"\bsk-ant-[A-Za-z0-9_\-]{20,}"),
+        Rule::new("openai-key", r"\bsk-(?:proj-)?[A-Za-z0-9_\-]{20,}"),
+        Rule::new(
+            "jwt",
+            r"\beyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}",
+        ),
+        // A value assigned to a secret-looking name, either quoted or as the
+        // bare remainder of the line (`.env`, YAML).
+        Rule::entropy(
+            "secret",
+            r#"(?im)(?:password|passwd|secret|token|api_?key|access_?key|auth_?key)[A-Za-z0-9_]*["']?\s*[:=]\s*(?:["']([^"'\s]{12,})["']|([A-Za-z0-9/+=_\-!@#$%^&*~]{12,})\s*$)"#,
+            ASSIGNMENT_ENTROPY_THRESHOLD,
+        ),
+        Rule::entropy(
+            "high-entropy",
+            r#"["']([A-Za-z0-9/+=_\-]{32,})["']"#,
+            ENTROPY_THRESHOLD,
+        ),
+    ]
+});
+
+/// A possible secret found in a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub kind: &'static str,
+    /// 1-based line the secret starts on.
+    pub line: usize,
+    /// Byte range of the secret in the scanned text.
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds credentials in file contents before they leave the machine.
+#[derive(Default)]
+pub struct Scanner {
+    allow: Vec<Regex>,
+}
+
+impl Scanner {
+    /// A scanner that ignores any secret matched by one of `allow`.
+    #[must_use]
+    pub const fn new(allow: Vec<Regex>) -> Self {
+        Self { allow }
+    }
+
+    /// Read an allowlist file: one regular expression per line, matched
+    /// against the secret itself. Blank lines and `#` comments are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or a pattern is invalid.
+    pub fn load_allowlist(path: &Path) -> anyhow::Result<Vec<Regex>> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("cannot read allowlist {}: {e}", path.display()))?;
+        text.lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                Regex::new(l).map_err(|e| {
+                    anyhow::anyhow!("invalid allowlist pattern in {}: {e}", path.display())
+                })
+            })
+            .collect()
+    }
+
+    /// All non-overlapping findings in `content`, in order of appearance.
+    #[must_use]
+    pub fn scan(&self, content: &str) -> Vec<Finding> {
+        let mut found: Vec<Finding> = Vec::new();
+        for rule in RULES.iter() {
+            for caps in rule.re.captures_iter(content) {
+                let Some(m) = caps.iter().skip(1).flatten().next().or_else(|| caps.get(0)) else {
+                    continue;
+                };
+                let secret = m.as_str();
+                if rule.min_entropy.is_some_and(|min| entropy(secret) < min)
+                    || (rule.kind == "high-entropy" && is_digest(secret))
+                    || self.allow.iter().any(|re| re.is_match(secret))
+                    || found.iter().any(|f| m.start() < f.end && f.start < m.end())
+                {
+                    continue;
+                }
+                found.push(Finding {
+                    kind: rule.kind,
+                    line: content[..m.start()].matches('\n').count() + 1,
+                    start: m.start(),
+                    end: m.end(),
+                });
+            }
+        }
+        found.sort_by_key(|f| f.start);
+        found
+    }
+}
+
+/// Replace every finding with a `[REDACTED:kind]` marker. Newlines inside a
+/// redacted span are kept so line numbers still match the original file.
+#[must_use]
+pub fn redact(content: &str, findings: &[Finding]) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for f in findings {
+        out.push_str(&content[last..f.start]);
+        out.push_str("[REDACTED:");
+        out.push_str(f.kind);
+        out.push(']');
+        let newlines = content[f.start..f.end].matches('\n').count();
+        out.extend(std::iter::repeat_n('\n', newlines));
+        last = f.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// Whether `s` is a checksum rather than a credential: a Subresource
+/// Integrity value (`sha512-...`, as in lockfiles) or a hex digest.
+fn is_digest(s: &str) -> bool {
+    static SRI: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^(?:sha(?:1|256|384|512)|md5)[-:][A-Za-z0-9+/_\-]+={0,2}$").unwrap()
+    });
+    SRI.is_match(s) || s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Shannon entropy of `s` in bits per byte.
+#[allow(clippy::cast_precision_loss)]
+fn entropy(s: &str) -> f64 {
+    let mut counts = [0_usize; 256];
+    for b in s.bytes() {
+        counts[usize::from(b)] += 1;
+    }
+    let len = s.len() as f64;
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
@@ -0,0 +1,121 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::budget::{estimate_tokens, parse_budget};
+use std::ops::Range;
+
+/// Maximum size of each part made by `--split`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitLimit {
+    Tokens(usize),
+    Bytes(usize),
+}
+
+impl SplitLimit {
+    /// Parse a `--split` value: a token count in the syntax of `--budget`
+    /// (`30k`, `claude-200k`), or a byte count ending in `b` (`60kb`,
+    /// `65536b`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error message if the value is not a valid size.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let trimmed = s.trim();
+        trimmed
+            .strip_suffix(['b', 'B'])
+            .map_or_else(
+                || parse_budget(trimmed).map(Self::Tokens),
+                |n| {
+                    parse_budget(n).map(Self::Bytes).map_err(|_| {
+                        format!(
+                            "Invalid split size '{s}'. Use a token count such as 30k or a byte count such as 60kb"
+                        )
+                    })
+                },
+            )
+    }
+
+    /// The size of `text` in this limit's unit.
+    #[must_use]
+    pub const fn measure(self, text: &str) -> usize {
+        match self {
+            Self::Tokens(_) => estimate_tokens(text),
+            Self::Bytes(_) => text.len(),
+        }
+    }
+
+    #[must_use]
+    pub const fn limit(self) -> usize {
+        match self {
+            Self::Tokens(n) | Self::Bytes(n) => n,
+        }
+    }
+
+    #[must_use]
+    pub const fn unit(self) -> &'static str {
+        match self {
+            Self::Tokens(_) => "tokens",
+            Self::Bytes(_) => "bytes",
+        }
+    }
+}
+
+/// Cut `lines` into consecutive ranges for which `fits` holds.
+///
+/// Each range is made as long as possible, then shortened to end after a
+/// blank line or, failing that, before an unindented line (the start of the
+/// next top-level item), as long as that keeps at least half of it. A single
+/// line that does not fit becomes a range of its own.
+pub fn split_lines(lines: &[&str], fits: impl Fn(Range<usize>) -> bool) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        let end = longest_fit(start, lines.len(), &fits);
+        let end = if end == lines.len() {
+            end
+        } else {
+            let min = start + (end - start).div_ceil(2);
+            let blank = (min..end).rev().find(|&e| lines[e - 1].trim().is_empty());
+            let top_level = || {
+                (min..end).rev().find(|&e| {
+                    lines[e].starts_with(|c: char| !c.is_whitespace() && !"})]".contains(c))
+                })
+            };
+            blank.or_else(top_level).unwrap_or(end)
+        };
+        ranges.push(start..end);
+        start = end;
+    }
+    ranges
+}
+
+/// The largest `end` in `start + 1..=len` with `fits(start..end)`, found by
+/// binary search; `start + 1` if even a single line does not fit.
+fn longest_fit(start: usize, len: usize, fits: &impl Fn(Range<usize>) -> bool) -> usize {
+    let (mut lo, mut hi) = (start + 1, len);
+    if fits(start..hi) {
+        return hi;
+    }
+    while lo < hi {
+        let mid = (lo + hi).div_ceil(2);
+        if fits(start..mid) {
+            lo = mid;
+        } else {
+            hi = mid - 1;
+        }
+    }
+    lo
+}
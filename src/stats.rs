@@ -0,0 +1,144 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::budget::estimate_tokens;
+use crate::language::language_name;
+use serde::Serialize;
+use std::path::Path;
+
+/// How many entries the "largest files" list keeps.
+const LARGEST: usize = 5;
+
+/// Summary of a bundle, printed by `--stats`.
+#[derive(Serialize, Debug, Default)]
+pub struct Stats {
+    pub files: usize,
+    pub skipped: usize,
+    pub lines: usize,
+    pub bytes: usize,
+    pub tokens: usize,
+    pub languages: Vec<LanguageStats>,
+    pub largest: Vec<FileStats>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct FileStats {
+    pub path: String,
+    pub lines: usize,
+    pub bytes: usize,
+}
+
+impl Stats {
+    /// Gather statistics over `(display path, content)` pairs. `skipped`
+    /// counts files that were collected but not printed (unreadable, or left
+    /// out by `--budget`).
+    #[must_use]
+    pub fn collect<'a, I>(files: I, skipped: usize) -> Self
+    where
+        I: IntoIterator<Item = (&'a Path, &'a str)>,
+    {
+        let mut stats = Self {
+            skipped,
+            ..Self::default()
+        };
+        let mut all = Vec::new();
+        for (path, content) in files {
+            let lines = content.lines().count();
+            let bytes = content.len();
+            stats.files += 1;
+            stats.lines += lines;
+            stats.bytes += bytes;
+            stats.tokens += estimate_tokens(content);
+
+            let language = language_name(path, content).to_owned();
+            if let Some(entry) = stats.languages.iter_mut().find(|l| l.language == language) {
+                entry.files += 1;
+                entry.lines += lines;
+                entry.bytes += bytes;
+            } else {
+                stats.languages.push(LanguageStats {
+                    language,
+                    files: 1,
+                    lines,
+                    bytes,
+                });
+            }
+            all.push(FileStats {
+                path: path.display().to_string(),
+                lines,
+                bytes,
+            });
+        }
+        stats
+            .languages
+            .sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.language.cmp(&b.language)));
+        all.sort_by(|a, b| b.bytes.cmp(&a.bytes).then(a.path.cmp(&b.path)));
+        all.truncate(LARGEST);
+        stats.largest = all;
+        stats
+    }
+
+    /// Human-readable summary, one entry per line.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let mut out = vec![
+            format!("Files: {} ({} skipped)", self.files, self.skipped),
+            format!(
+                "Lines: {}  Bytes: {}  Tokens (est.): {}",
+                self.lines, self.bytes, self.tokens
+            ),
+        ];
+        if !self.languages.is_empty() {
+            out.push("Languages:".to_owned());
+            let width = self
+                .languages
+                .iter()
+                .map(|l| l.language.chars().count())
+                .max()
+                .unwrap_or(0);
+            for l in &self.languages {
+                out.push(format!(
+                    "  {:<width$}  {} files  {} lines  {} bytes",
+                    l.language, l.files, l.lines, l.bytes
+                ));
+            }
+        }
+        if !self.largest.is_empty() {
+            out.push("Largest files:".to_owned());
+            let width = self
+                .largest
+                .iter()
+                .map(|f| f.path.chars().count())
+                .max()
+                .unwrap_or(0);
+            for f in &self.largest {
+                out.push(format!(
+                    "  {:<width$}  {} bytes  {} lines",
+                    f.path, f.bytes, f.lines
+                ));
+            }
+        }
+        out
+    }
+}
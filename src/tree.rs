@@ -0,0 +1,158 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use crate::cli::TreeInfo;
+use serde::Serialize;
+use std::path::Path;
+
+/// One file or directory in the `--tree` overview.
+#[derive(Serialize, Debug, Default)]
+pub struct TreeNode {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Self>,
+}
+
+impl TreeNode {
+    #[must_use]
+    pub const fn is_dir(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    fn annotation(&self) -> String {
+        match (self.size, self.lines) {
+            (Some(size), _) => format!(" ({size} bytes)"),
+            (_, Some(lines)) => format!(" ({lines} lines)"),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Directory tree of the files in a bundle, built from their display paths.
+#[derive(Serialize, Debug, Default)]
+#[serde(transparent)]
+pub struct Tree {
+    pub roots: Vec<TreeNode>,
+}
+
+impl Tree {
+    /// Build a tree from `(display path, content)` pairs. Children are sorted
+    /// by name and chains of single-child directories are collapsed into one
+    /// node (`src/formatters`) to keep deep paths readable.
+    #[must_use]
+    pub fn build<'a, I>(files: I, info: TreeInfo) -> Self
+    where
+        I: IntoIterator<Item = (&'a Path, &'a str)>,
+    {
+        let mut root = TreeNode::default();
+        for (path, content) in files {
+            let mut node = &mut root;
+            for part in path {
+                let name = part.to_string_lossy();
+                let idx = if let Some(idx) = node.children.iter().position(|c| c.name == name) {
+                    idx
+                } else {
+                    node.children.push(TreeNode {
+                        name: name.into_owned(),
+                        ..TreeNode::default()
+                    });
+                    node.children.len() - 1
+                };
+                node = &mut node.children[idx];
+            }
+            match info {
+                TreeInfo::Size => node.size = Some(content.len()),
+                TreeInfo::Lines => node.lines = Some(content.lines().count()),
+                TreeInfo::None => {}
+            }
+        }
+        let mut roots = root.children;
+        for node in &mut roots {
+            finish(node, info);
+        }
+        roots.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { roots }
+    }
+
+    /// Render the tree as text lines, using box-drawing connectors when
+    /// `utf8` is set and `|--`/`` `-- `` otherwise.
+    #[must_use]
+    pub fn lines(&self, utf8: bool) -> Vec<String> {
+        let mut out = Vec::new();
+        for root in &self.roots {
+            out.push(format!("{}{}", label(root), root.annotation()));
+            render_children(root, "", utf8, &mut out);
+        }
+        out
+    }
+}
+
+/// Sort children, collapse single-child directory chains and add up the
+/// sizes or line counts of directories.
+fn finish(node: &mut TreeNode, info: TreeInfo) {
+    while node.children.len() == 1 && node.children[0].is_dir() {
+        let child = node.children.remove(0);
+        node.name = if node.name.ends_with('/') {
+            format!("{}{}", node.name, child.name)
+        } else {
+            format!("{}/{}", node.name, child.name)
+        };
+        node.children = child.children;
+    }
+    if !node.is_dir() {
+        return;
+    }
+    for child in &mut node.children {
+        finish(child, info);
+    }
+    node.children.sort_by(|a, b| a.name.cmp(&b.name));
+    match info {
+        TreeInfo::Size => node.size = Some(node.children.iter().filter_map(|c| c.size).sum()),
+        TreeInfo::Lines => node.lines = Some(node.children.iter().filter_map(|c| c.lines).sum()),
+        TreeInfo::None => {}
+    }
+}
+
+fn label(node: &TreeNode) -> String {
+    if node.is_dir() && !node.name.ends_with('/') {
+        format!("{}/", node.name)
+    } else {
+        node.name.clone()
+    }
+}
+
+fn render_children(node: &TreeNode, prefix: &str, utf8: bool, out: &mut Vec<String>) {
+    let (tee, elbow, pipe) = if utf8 {
+        ("├── ", "└── ", "│   ")
+    } else {
+        ("|-- ", "`-- ", "|   ")
+    };
+    let last = node.children.len().saturating_sub(1);
+    for (idx, child) in node.children.iter().enumerate() {
+        let connector = if idx == last { elbow } else { tee };
+        out.push(format!(
+            "{prefix}{connector}{}{}",
+            label(child),
+            child.annotation()
+        ));
+        let extension = if idx == last { "    " } else { pipe };
+        render_children(child, &format!("{prefix}{extension}"), utf8, out);
+    }
+}
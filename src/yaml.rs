@@ -0,0 +1,156 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+//! A small YAML emitter for `--format yaml`.
+//!
+//! Values go through `serde_json::Value`, so the YAML carries exactly the
+//! fields of the JSON document. Multi-line strings are written as literal
+//! block scalars (`|`), which keeps source code readable; everything else is
+//! a JSON scalar, which is also valid YAML.
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Serialise `value` as a YAML document.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be represented as JSON.
+pub fn to_string<T: Serialize>(value: &T) -> serde_json::Result<String> {
+    let value = serde_json::to_value(value)?;
+    let mut out = String::new();
+    match &value {
+        Value::Object(map) if !map.is_empty() => mapping(&mut out, map, 0, false),
+        Value::Array(items) if !items.is_empty() => sequence(&mut out, items, 0),
+        other => {
+            scalar(&mut out, other, 0, false);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Write the entries of `map` at `indent`. With `inline` the first key
+/// follows a `- ` already on the line.
+fn mapping(out: &mut String, map: &Map<String, Value>, indent: usize, inline: bool) {
+    for (idx, (key, value)) in map.iter().enumerate() {
+        if idx > 0 || !inline {
+            pad(out, indent);
+        }
+        out.push_str(key);
+        out.push(':');
+        match value {
+            Value::Object(inner) if !inner.is_empty() => {
+                out.push('\n');
+                mapping(out, inner, indent + 2, false);
+            }
+            Value::Array(items) if !items.is_empty() => {
+                out.push('\n');
+                sequence(out, items, indent + 2);
+            }
+            _ => {
+                out.push(' ');
+                scalar(out, value, indent, true);
+                out.push('\n');
+            }
+        }
+    }
+}
+
+fn sequence(out: &mut String, items: &[Value], indent: usize) {
+    for item in items {
+        pad(out, indent);
+        match item {
+            Value::Object(map) if !map.is_empty() => {
+                out.push_str("- ");
+                mapping(out, map, indent + 2, true);
+            }
+            Value::Array(inner) if !inner.is_empty() => {
+                out.push_str("-\n");
+                sequence(out, inner, indent + 2);
+            }
+            _ => {
+                out.push_str("- ");
+                scalar(out, item, indent, false);
+                out.push('\n');
+            }
+        }
+    }
+}
+
+/// Write a scalar, or an empty collection, without the trailing newline.
+/// `indent` is that of the enclosing mapping or sequence; block scalars are
+/// indented two columns further. `keyed` tells whether the value follows a
+/// mapping key, which is where an indentation indicator can be used.
+fn scalar(out: &mut String, value: &Value, indent: usize, keyed: bool) {
+    match value {
+        Value::String(s) if literal_ok(s, keyed) => literal(out, s, indent + 2),
+        Value::Object(_) => out.push_str("{}"),
+        Value::Array(_) => out.push_str("[]"),
+        // JSON scalars are valid YAML flow scalars, and the JSON string
+        // escapes are a subset of YAML's double-quoted ones.
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// Whether `s` can be written as a literal block scalar: it must span
+/// several lines and contain only characters YAML allows there unescaped.
+fn literal_ok(s: &str, keyed: bool) -> bool {
+    let body = s.trim_end_matches('\n');
+    body.contains('\n')
+        && !s
+            .chars()
+            .any(|c| (c.is_control() && c != '\n' && c != '\t') || c == '\u{feff}')
+        // Leading blanks need an indentation indicator, which is only
+        // unambiguous after a mapping key.
+        && (keyed || !first_line_indented(body))
+}
+
+fn first_line_indented(body: &str) -> bool {
+    body.lines()
+        .find(|line| !line.is_empty())
+        .is_some_and(|line| line.starts_with([' ', '\t']))
+}
+
+/// Write `s` as `|` with the chomping indicator that keeps its trailing
+/// newlines exactly, followed by its lines at `indent`.
+fn literal(out: &mut String, s: &str, indent: usize) {
+    let body = s.trim_end_matches('\n');
+    let newlines = s.len() - body.len();
+    out.push('|');
+    if first_line_indented(body) {
+        out.push('2');
+    }
+    out.push_str(match newlines {
+        0 => "-",
+        1 => "",
+        _ => "+",
+    });
+    for line in body.split('\n') {
+        out.push('\n');
+        if !line.is_empty() {
+            pad(out, indent);
+            out.push_str(line);
+        }
+    }
+    for _ in 1..newlines {
+        out.push('\n');
+    }
+}
+
+fn pad(out: &mut String, indent: usize) {
+    out.extend(std::iter::repeat_n(' ', indent));
+}
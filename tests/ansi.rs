@@ -0,0 +1,160 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use assert_cmd::Command;
+use rucat::cli::LineWrap;
+use rucat::color::{ColorLevel, StyleColor, TextStyle};
+use rucat::formatters::{
+    Formatter,
+    ansi::{Ansi, AnsiStyle},
+    utf8::Utf8,
+};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn ansi(color: ColorLevel, highlight: bool) -> Ansi {
+    Ansi {
+        width: 30,
+        line_numbers: true,
+        tabs: 4,
+        wrap: LineWrap::Wrap,
+        color,
+        style: AnsiStyle::default(),
+        highlight,
+        syntax_override: None,
+        theme: None,
+    }
+}
+
+fn capture<F: Formatter>(fmt: &F, content: &str) -> String {
+    let mut buf = Vec::new();
+    fmt.write(Path::new("a.rs"), content, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn plain_without_colours() {
+    let out = capture(&ansi(ColorLevel::None, true), "fn main() {}\n");
+    assert!(!out.contains('\x1b'), "{out}");
+    let utf8 = Utf8 {
+        width: 30,
+        line_numbers: true,
+        tabs: 4,
+        wrap: LineWrap::Wrap,
+    };
+    // The same box as utf8, bar the header's trailing space.
+    assert_eq!(
+        out.lines().nth(3),
+        capture(&utf8, "fn main() {}\n").lines().nth(3)
+    );
+}
+
+#[test]
+fn styles_borders_header_and_gutter() {
+    let out = capture(&ansi(ColorLevel::TrueColor, false), "fn main() {}\n");
+    let lines: Vec<&str> = out.lines().collect();
+    assert!(lines[0].starts_with("\x1b[34m┌"), "{out}");
+    assert!(lines[1].contains("\x1b[1m File: a.rs"), "{out}");
+    assert_eq!(
+        lines[3],
+        format!(
+            "\x1b[34m│\x1b[0m\x1b[2m1 │\x1b[0m fn main() {{}}{}\x1b[34m│\x1b[0m",
+            " ".repeat(14)
+        )
+    );
+}
+
+#[test]
+fn highlights_inside_the_box() {
+    let plain = capture(&ansi(ColorLevel::TrueColor, false), "fn main() {}\n");
+    let highlighted = capture(&ansi(ColorLevel::TrueColor, true), "fn main() {}\n");
+    assert!(highlighted.contains("\x1b[38;2;"), "{highlighted}");
+    assert!(!plain.contains("\x1b[38;2;"), "{plain}");
+    // Padding still lines the right border up.
+    let row = highlighted.lines().nth(3).unwrap();
+    assert!(
+        row.ends_with(&format!("\x1b[0m{}\x1b[34m│\x1b[0m", " ".repeat(14))),
+        "{row:?}"
+    );
+}
+
+#[test]
+fn syntax_override_changes_the_highlighting() {
+    let content = "# note\n";
+    let as_rust = capture(&ansi(ColorLevel::TrueColor, true), content);
+    let as_python = capture(
+        &Ansi {
+            syntax_override: Some("python".to_owned()),
+            ..ansi(ColorLevel::TrueColor, true)
+        },
+        content,
+    );
+    assert_ne!(as_rust, as_python);
+}
+
+#[test]
+fn parses_text_styles() {
+    let style: TextStyle = "bold bright-red".parse().unwrap();
+    assert!(style.bold && !style.dim);
+    assert_eq!(style.color, Some(StyleColor::Named(9)));
+    assert_eq!(style.escape(ColorLevel::Ansi256), "\x1b[1m\x1b[91m");
+    assert_eq!(style.escape(ColorLevel::None), "");
+
+    let style: TextStyle = "underline #ff8700".parse().unwrap();
+    assert_eq!(style.color, Some(StyleColor::Rgb(0xff, 0x87, 0x00)));
+    assert_eq!(style.escape(ColorLevel::Ansi256), "\x1b[4m\x1b[38;5;208m");
+
+    assert!("purple".parse::<TextStyle>().is_err());
+    assert!("red blue".parse::<TextStyle>().is_err());
+    assert!("#12345".parse::<TextStyle>().is_err());
+}
+
+#[test]
+fn styles_come_from_the_config() {
+    let home = tempdir().unwrap();
+    let config = if cfg!(target_os = "macos") {
+        home.path().join("Library/Application Support/rucat")
+    } else {
+        home.path().join(".config/rucat")
+    };
+    std::fs::create_dir_all(&config).unwrap();
+    std::fs::write(
+        config.join("config.toml"),
+        "[ansi_style]\nborder = \"green\"\ngutter = \"italic\"\n",
+    )
+    .unwrap();
+    let file = home.path().join("a.txt");
+    std::fs::write(&file, "hello\n").unwrap();
+
+    let run = |color: &str| {
+        let out = Command::cargo_bin("rucat")
+            .unwrap()
+            .env("HOME", home.path())
+            .args(["-f", "ansi", "-n", "--ansi-width", "20", "--color", color])
+            .arg(&file)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    let out = run("always");
+    assert!(out.starts_with("\x1b[32m┌"), "{out}");
+    assert!(out.contains("\x1b[3m1 │\x1b[0m hello"), "{out}");
+    // The header keeps its default style.
+    assert!(out.contains("\x1b[1m File: "), "{out}");
+    assert!(!run("never").contains('\x1b'));
+}
@@ -0,0 +1,207 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::budget::{Candidate, Verdict, parse_budget, plan};
+use tempfile::tempdir;
+
+#[test]
+fn parses_numbers_suffixes_and_presets() {
+    assert_eq!(parse_budget("2000"), Ok(2000));
+    assert_eq!(parse_budget("100k"), Ok(100_000));
+    assert_eq!(parse_budget("1.5M"), Ok(1_500_000));
+    assert_eq!(parse_budget("claude-200k"), Ok(200_000));
+    assert!(parse_budget("lots").is_err());
+    // Only whole tokens, without exponents, and at least one of them.
+    for bad in [
+        "0", "0.1", "0.0k", "1e3", "1.5e2k", "1.25k", "inf", "-5", "+5", ".5k",
+    ] {
+        assert!(parse_budget(bad).is_err(), "{bad}");
+    }
+    assert!(parse_budget("99999999999999999999").is_err());
+    assert!(parse_budget(&format!("{}m", usize::MAX)).is_err());
+}
+
+#[test]
+fn lowest_rank_is_dropped_first() {
+    let big = "x".repeat(400);
+    let candidates = [
+        Candidate {
+            content: &big,
+            rank: 0,
+            must: false,
+            cost: 110,
+            overhead: 10,
+        },
+        Candidate {
+            content: &big,
+            rank: 1,
+            must: false,
+            cost: 110,
+            overhead: 10,
+        },
+    ];
+    let verdicts = plan(&candidates, 150).unwrap();
+    assert_eq!(verdicts, vec![Verdict::Keep, Verdict::Omit]);
+}
+
+#[test]
+fn smaller_files_still_fit_after_a_truncation() {
+    let big = "line\n".repeat(400);
+    let small = "x\n";
+    let candidates = [
+        Candidate {
+            content: &big,
+            rank: 0,
+            must: false,
+            cost: 510,
+            overhead: 10,
+        },
+        Candidate {
+            content: small,
+            rank: 1,
+            must: false,
+            cost: 11,
+            overhead: 10,
+        },
+    ];
+    let verdicts = plan(&candidates, 200).unwrap();
+    let Verdict::Truncate(keep) = verdicts[0] else {
+        panic!("{verdicts:?}");
+    };
+    // 200 tokens less 10 of overhead and 16 for the marker, at 500 tokens
+    // for 2000 bytes, less the 11 the small file needs.
+    assert!(keep > 0 && keep <= 4 * (200 - 26 - 11), "{keep}");
+    assert_eq!(verdicts[1], Verdict::Keep);
+}
+
+#[test]
+fn must_files_that_do_not_fit_are_an_error() {
+    let big = "x".repeat(4000);
+    let candidates = [Candidate {
+        content: &big,
+        rank: 0,
+        must: true,
+        cost: 1010,
+        overhead: 10,
+    }];
+    assert!(plan(&candidates, 100).is_err());
+}
+
+#[test]
+fn cli_budget_notes_omitted_files() {
+    let dir = tempdir().unwrap();
+    let first = prepare_file(dir.path(), "first.txt", &"keep me\n".repeat(20));
+    let second = prepare_file(dir.path(), "second.txt", &"drop me\n".repeat(200));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "markdown", "--budget", "100"])
+        .arg(&first)
+        .arg(&second)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("keep me")
+                .and(predicate::str::contains("drop me").not())
+                .and(predicate::str::contains("omitted 1 file(s)")),
+        );
+}
+
+#[test]
+fn cli_must_flag_wins_over_argument_order() {
+    let dir = tempdir().unwrap();
+    let first = prepare_file(dir.path(), "first.txt", &"first\n".repeat(50));
+    let second = prepare_file(dir.path(), "second.txt", &"second\n".repeat(50));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "ascii"])
+        .arg(&first)
+        .arg(&second)
+        .args(["--budget", "120", "--must"])
+        .arg(&second)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("second").and(predicate::str::contains("first\n").not()));
+}
+
+/// The whole output, with its gutters, escaping and prompt, fits the budget.
+#[test]
+fn cli_budget_counts_the_rendered_output() {
+    let dir = tempdir().unwrap();
+    let files: Vec<_> = (0..8)
+        .map(|i| prepare_file(dir.path(), &format!("f{i}.txt"), &"a < b && c\n".repeat(60)))
+        .collect();
+    let prompt = "p".repeat(2000);
+    for args in [&["-f", "xml", "-n"][..], &["--prompt", &prompt]] {
+        let out = Command::cargo_bin("rucat")
+            .unwrap()
+            .env("HOME", dir.path())
+            .args(["--budget", "1k"])
+            .args(args)
+            .args(&files)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        let text = String::from_utf8(out.stdout).unwrap();
+        assert!(rucat::budget::estimate_tokens(&text) <= 1000, "{args:?}");
+        assert!(text.contains("f0.txt") && text.contains("budget of 1000 tokens reached"));
+    }
+}
+
+#[test]
+fn cli_must_matches_paths_given_with_dot() {
+    let dir = tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    prepare_file(dir.path(), "first.txt", &"first\n".repeat(50));
+    prepare_file(&dir.path().join("src"), "x.rs", &"second\n".repeat(50));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "ascii", "first.txt", "src/x.rs"])
+        .args(["--budget", "120", "--must", "./src"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("second").and(predicate::str::contains("first\n").not()));
+}
+
+#[test]
+fn cli_must_overflow_fails() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "big.txt", &"x".repeat(4000));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--budget", "100", "--must"])
+        .arg(&file)
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must-have files need"));
+}
+
+#[test]
+fn cli_invalid_budget_fails() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--budget", "lots"])
+        .write_stdin("x")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid budget 'lots'"));
+}
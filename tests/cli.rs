@@ -14,22 +14,15 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
 use assert_cmd::Command;
+use common::prepare_file;
 use predicates::prelude::*;
 use std::fs::File;
 use std::io::Write;
 use tempfile::tempdir;
 
-fn prepare_file(dir: &std::path::Path, name: &str, body: &str) -> std::path::PathBuf {
-    let p = dir.join(name);
-    if let Some(parent) = p.parent() {
-        std::fs::create_dir_all(parent).unwrap(); // ensure nested dirs exist
-    }
-    let mut f = File::create(&p).unwrap();
-    write!(f, "{body}").unwrap();
-    p
-}
-
 #[test]
 fn cli_ascii_numbers() {
     let dir = tempdir().unwrap();
@@ -101,7 +94,8 @@ fn cli_pretty_default_by_extension() {
     Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -118,7 +112,15 @@ fn cli_pretty_syntax_flag_overrides_extension() {
     let out_rust = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty", "--pretty-syntax", "rust"])
+        .env("COLORTERM", "truecolor")
+        .args([
+            "-f",
+            "pretty",
+            "--color",
+            "always",
+            "--pretty-syntax",
+            "rust",
+        ])
         .arg(&file)
         .assert()
         .success()
@@ -129,7 +131,8 @@ fn cli_pretty_syntax_flag_overrides_extension() {
     let out_plain = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -152,7 +155,8 @@ fn cli_pretty_modeline_overrides_extension() {
     Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -170,7 +174,8 @@ fn cli_pretty_flag_overrides_modeline() {
     let out_sh = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty", "--pretty-syntax", "sh"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always", "--pretty-syntax", "sh"])
         .arg(&file)
         .assert()
         .success()
@@ -183,7 +188,8 @@ fn cli_pretty_flag_overrides_modeline() {
     let out_toml = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -220,7 +226,8 @@ fn cli_pretty_config_file_is_used() {
     Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -250,7 +257,8 @@ fn cli_pretty_config_is_overridden_by_flag() {
     let out_sh_from_flag = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty", "--pretty-syntax", "sh"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always", "--pretty-syntax", "sh"])
         .arg(&file)
         .assert()
         .success()
@@ -262,7 +270,8 @@ fn cli_pretty_config_is_overridden_by_flag() {
     let out_toml_from_config = Command::cargo_bin("rucat")
         .unwrap()
         .env("HOME", home_dir.path())
-        .args(["-f", "pretty"])
+        .env("COLORTERM", "truecolor")
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .assert()
         .success()
@@ -276,3 +285,32 @@ fn cli_pretty_config_is_overridden_by_flag() {
         "Highlighting from CLI flag should override config file"
     );
 }
+
+#[test]
+fn cli_pretty_decorations() {
+    let dir = tempdir().unwrap();
+    let home_dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    let b = prepare_file(dir.path(), "b.py", "print(1)\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home_dir.path())
+        .args(["-f", "pretty", "--decorations", "header"])
+        .arg(&a)
+        .arg(&b)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("a.rs\nSize: 13 B, Language: Rust\nfn main() {}\n\n").and(
+                predicate::str::contains("b.py\nSize: 9 B, Language: Python\n"),
+            ),
+        );
+
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "pretty", "--decorations", "boxes"])
+        .arg(&a)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("boxes"));
+}
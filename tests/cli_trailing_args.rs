@@ -14,19 +14,13 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
 use assert_cmd::Command;
+use common::prepare_file;
 use predicates::prelude::*;
-use std::fs::File;
-use std::io::Write;
 use tempfile::tempdir;
 
-fn prepare_file(dir: &std::path::Path, name: &str, body: &str) -> std::path::PathBuf {
-    let p = dir.join(name);
-    let mut f = File::create(&p).unwrap();
-    write!(f, "{body}").unwrap();
-    p
-}
-
 #[test]
 fn trailing_numbers_flag() {
     let dir = tempdir().unwrap();
@@ -162,7 +156,7 @@ fn trailing_pretty_syntax_flag() {
     // Test --pretty-syntax after file
     Command::cargo_bin("rucat")
         .unwrap()
-        .args(["-f", "pretty"])
+        .args(["-f", "pretty", "--color", "always"])
         .arg(&file)
         .args(["--pretty-syntax", "rust"])
         .assert()
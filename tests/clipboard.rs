@@ -33,6 +33,91 @@ mod clipboard_tests {
             .stdout(predicate::str::contains("hello world"));
     }
 
+    // OSC sequences have to reach the terminal, so --copy never pages.
+    #[test]
+    fn copy_turns_paging_off() {
+        let dir = tempdir().unwrap();
+        let file = prepare_file(dir.path(), "a.txt", "hello world");
+        Command::cargo_bin("rucat")
+            .unwrap()
+            .env("RUCAT_PAGER", "sed s/^/paged:/")
+            .args(["--paging", "always", "--copy"])
+            .args(["--clipboard-provider-for-test", "osc52"])
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("paged:").not());
+    }
+
+    // Files written to a directory leave nothing to copy.
+    #[test]
+    fn copy_rejects_output_and_split_dirs() {
+        let dir = tempdir().unwrap();
+        let file = prepare_file(dir.path(), "a.txt", "hello world");
+        for args in [
+            &["--output-dir", "out"][..],
+            &["--split", "10k", "--split-dir", "parts"],
+        ] {
+            Command::cargo_bin("rucat")
+                .unwrap()
+                .current_dir(dir.path())
+                .args(["--copy", "--clipboard-provider-for-test", "osc52"])
+                .args(args)
+                .arg(&file)
+                .assert()
+                .failure()
+                .stderr(predicate::str::contains("--copy cannot be used with"));
+        }
+    }
+
+    // Secrets are not copied unless they are redacted or scanning is off.
+    #[test]
+    fn copy_refuses_secrets_unless_redacted() {
+        let dir = tempdir().unwrap();
+        let key = format!("AKIA{}", "IOSFODNN7EXAMPLE");
+        let file = prepare_file(dir.path(), "a.txt", &key);
+        let run = |mode: &[&str]| {
+            let mut cmd = Command::cargo_bin("rucat").unwrap();
+            cmd.args(["--copy", "--clipboard-provider-for-test", "osc52"])
+                .args(mode)
+                .arg(&file);
+            cmd.assert()
+        };
+        run(&[])
+            .failure()
+            .stdout(predicate::str::is_empty())
+            .stderr(predicate::str::contains(
+                "refusing to copy 1 possible secret(s)",
+            ));
+        run(&["--secrets", "redact"])
+            .success()
+            .stdout(predicate::str::contains("[REDACTED:aws-key]"));
+    }
+
+    // With --output the file gets the text and stdout only the escape sequence.
+    #[test]
+    fn copy_with_output_keeps_the_file_clean() {
+        let dir = tempdir().unwrap();
+        let file = prepare_file(dir.path(), "a.txt", "hello world");
+        let bundle = dir.path().join("bundle.md");
+        Command::cargo_bin("rucat")
+            .unwrap()
+            .args([
+                "--copy",
+                "--clipboard-provider-for-test",
+                "osc52",
+                "--output",
+            ])
+            .arg(&bundle)
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with("\x1b]52;c;"));
+        let written = std::fs::read_to_string(&bundle).unwrap();
+        assert!(written.contains("hello world"));
+        assert!(!written.contains('\x1b'));
+    }
+
     // This test is cross-platform. It forces the OSC 5522 provider via the hidden flag
     // to verify the correct escape sequence is generated on any OS.
     #[test]
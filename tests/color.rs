@@ -0,0 +1,143 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use rucat::cli::ColorMode;
+use rucat::color::ColorLevel;
+use syntect::highlighting::Color;
+use tempfile::tempdir;
+
+/// Detects the level with only the variables in `vars` set.
+fn detect(mode: ColorMode, to_terminal: bool, vars: &[(&str, &str)]) -> ColorLevel {
+    ColorLevel::detect_with(mode, to_terminal, |name| {
+        vars.iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| (*v).to_string())
+    })
+}
+
+const fn rgb(r: u8, g: u8, b: u8) -> Color {
+    Color { r, g, b, a: 0xff }
+}
+
+#[test]
+fn auto_needs_a_terminal() {
+    let vars = [("COLORTERM", "truecolor")];
+    assert_eq!(detect(ColorMode::Auto, false, &vars), ColorLevel::None);
+    assert_eq!(detect(ColorMode::Auto, true, &vars), ColorLevel::TrueColor);
+    assert_eq!(
+        detect(ColorMode::Always, false, &vars),
+        ColorLevel::TrueColor
+    );
+    assert_eq!(detect(ColorMode::Never, true, &vars), ColorLevel::None);
+}
+
+#[test]
+fn no_color_and_dumb_terminals_turn_auto_off() {
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("NO_COLOR", "1")]),
+        ColorLevel::None
+    );
+    // An empty NO_COLOR does not count.
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("NO_COLOR", "")]),
+        ColorLevel::Ansi16
+    );
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("TERM", "dumb")]),
+        ColorLevel::None
+    );
+    assert_eq!(
+        detect(ColorMode::Always, true, &[("NO_COLOR", "1")]),
+        ColorLevel::Ansi16
+    );
+}
+
+#[test]
+fn level_follows_colorterm_and_term() {
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("COLORTERM", "24bit")]),
+        ColorLevel::TrueColor
+    );
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("TERM", "xterm-256color")]),
+        ColorLevel::Ansi256
+    );
+    assert_eq!(
+        detect(ColorMode::Auto, true, &[("TERM", "xterm")]),
+        ColorLevel::Ansi16
+    );
+}
+
+#[test]
+fn escapes_are_quantised_to_the_level() {
+    let orange = rgb(0xff, 0x87, 0x00);
+    assert_eq!(
+        ColorLevel::TrueColor.escape(orange, false),
+        "\x1b[38;2;255;135;0m"
+    );
+    assert_eq!(ColorLevel::Ansi256.escape(orange, false), "\x1b[38;5;208m");
+    assert_eq!(ColorLevel::Ansi256.escape(orange, true), "\x1b[48;5;208m");
+    // Greys use the grey ramp rather than the colour cube.
+    assert_eq!(
+        ColorLevel::Ansi256.escape(rgb(0x30, 0x30, 0x30), false),
+        "\x1b[38;5;236m"
+    );
+    assert_eq!(
+        ColorLevel::Ansi16.escape(rgb(250, 10, 10), false),
+        "\x1b[91m"
+    );
+    assert_eq!(ColorLevel::Ansi16.escape(rgb(0, 0, 0), true), "\x1b[40m");
+    assert_eq!(ColorLevel::None.escape(orange, false), "");
+}
+
+#[test]
+fn pretty_is_plain_when_piped() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    let run = |args: &[&str]| {
+        let out = Command::cargo_bin("rucat")
+            .unwrap()
+            .env("HOME", dir.path())
+            .env("TERM", "xterm-256color")
+            .env_remove("COLORTERM")
+            .args(["-f", "pretty", "--decorations", "plain"])
+            .args(args)
+            .arg(&file)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8(out.stdout).unwrap()
+    };
+
+    assert_eq!(run(&[]), "fn main() {}\n");
+    assert_eq!(run(&["--color", "never"]), "fn main() {}\n");
+    let forced = run(&["--color", "always"]);
+    assert!(forced.contains("\x1b[38;5;"), "{forced:?}");
+    assert!(!forced.contains("\x1b[38;2;"), "{forced:?}");
+}
+
+#[test]
+fn rejects_unknown_color_modes() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--color", "sometimes"])
+        .assert()
+        .failure();
+}
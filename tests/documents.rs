@@ -0,0 +1,130 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::formatters::{FileMeta, Formatter, documents::Documents};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn capture(fmt: &Documents, path: &str, content: &str) -> String {
+    let mut buf = Vec::new();
+    let meta = FileMeta {
+        index: 2,
+        ..FileMeta::default()
+    };
+    fmt.write_file(Path::new(path), content, &meta, &mut buf)
+        .unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn document_is_escaped() {
+    let fmt = Documents {
+        line_numbers: false,
+        metadata: false,
+    };
+    assert_eq!(
+        capture(&fmt, "a&b.rs", "if a < b && c > d {}"),
+        "<document index=\"2\">\n<source>a&amp;b.rs</source>\n<document_content>\nif a &lt; b &amp;&amp; c &gt; d {}\n</document_content>\n</document>\n"
+    );
+}
+
+#[test]
+fn forbidden_control_characters_are_replaced() {
+    let fmt = Documents {
+        line_numbers: false,
+        metadata: false,
+    };
+    let out = capture(&fmt, "a.txt", "\x1b[1mbold\x0c\n");
+    assert!(out.contains("\u{fffd}[1mbold\u{fffd}\n"), "{out:?}");
+    roxmltree::Document::parse(&out).unwrap();
+}
+
+#[test]
+fn metadata_children_and_line_numbers() {
+    let fmt = Documents {
+        line_numbers: true,
+        metadata: true,
+    };
+    let out = capture(&fmt, "main.rs", "fn main() {}\n");
+    assert!(out.contains("<language>Rust</language>\n<lines>1</lines>\n<bytes>13</bytes>\n"));
+    assert!(out.contains("<document_content>\n1 | fn main() {}\n</document_content>"));
+}
+
+#[test]
+fn cli_wraps_files_in_documents_root() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/a.txt", "one\n");
+    prepare_file(dir.path(), "src/b.txt", "two\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "documents", "--strip", "1", "src/a.txt", "src/b.txt"])
+        .assert()
+        .success()
+        .stdout(
+            "<documents>\n\
+             <document index=\"1\">\n<source>a.txt</source>\n<document_content>\none\n</document_content>\n</document>\n\
+             <document index=\"2\">\n<source>b.txt</source>\n<document_content>\ntwo\n</document_content>\n</document>\n\
+             </documents>\n",
+        );
+}
+
+#[test]
+fn cli_tree_and_prompt_stay_inside_the_root() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "one\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "documents", "--tree", "--prompt", "Summarise."])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with(
+                "<documents>\n<instructions>Summarise.</instructions>\n<tree>",
+            )
+            .and(predicate::str::ends_with("</document>\n</documents>\n")),
+        );
+}
+
+#[cfg(feature = "clipboard")]
+#[test]
+fn cli_copies_documents() {
+    use base64::{Engine as _, engine::general_purpose};
+    let expected = "<documents>\n<document index=\"1\">\n<source>-</source>\n<document_content>\nhi\n</document_content>\n</document>\n</documents>\n";
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args([
+            "-f",
+            "documents",
+            "--copy",
+            "--clipboard-provider-for-test",
+            "osc52",
+        ])
+        .write_stdin("hi\n")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with(expected).and(predicate::str::contains(
+                general_purpose::STANDARD.encode(expected),
+            )),
+        );
+}
@@ -14,8 +14,16 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
+use rucat::cli::{Decorations, LineWrap, MarkdownFence, MarkdownHeader};
+use rucat::color::ColorLevel;
 use rucat::formatters::{
-    Formatter, ansi::Ansi, ascii::Ascii, markdown::Markdown, pretty::Pretty, utf8::Utf8, xml::Xml,
+    Formatter,
+    ansi::{Ansi, AnsiStyle},
+    ascii::Ascii,
+    markdown::Markdown,
+    pretty::Pretty,
+    utf8::Utf8,
+    xml::Xml,
 };
 use std::path::Path;
 
@@ -53,6 +61,13 @@ fn ansi_fixed_width() {
         &Ansi {
             width: 20,
             line_numbers: false,
+            tabs: 4,
+            wrap: LineWrap::Wrap,
+            color: ColorLevel::None,
+            style: AnsiStyle::default(),
+            highlight: false,
+            syntax_override: None,
+            theme: None,
         },
         "abc",
     );
@@ -67,6 +82,8 @@ fn utf8_numbers_width() {
         &Utf8 {
             width: 30,
             line_numbers: true,
+            tabs: 4,
+            wrap: LineWrap::Wrap,
         },
         "x",
     );
@@ -75,17 +92,31 @@ fn utf8_numbers_width() {
 
 #[test]
 fn markdown_block() {
-    let out = capture(&Markdown { line_numbers: true }, "fn main(){}");
+    let out = capture(
+        &Markdown {
+            line_numbers: true,
+            fence: MarkdownFence::default(),
+            header: MarkdownHeader::default(),
+        },
+        "fn main(){}",
+    );
     assert!(out.starts_with("---\nFile:"));
     assert!(out.contains("```rs") || out.contains("```")); // ext may be blank
 }
 
 #[test]
 fn xml_numbers_vs_plain() {
-    let with = capture(&Xml { line_numbers: true }, "a\nb");
+    let with = capture(
+        &Xml {
+            line_numbers: true,
+            cdata: false,
+        },
+        "a\nb",
+    );
     let no = capture(
         &Xml {
             line_numbers: false,
+            cdata: false,
         },
         "a\nb",
     );
@@ -99,6 +130,9 @@ fn pretty_highlighting() {
         &Pretty {
             line_numbers: true,
             syntax_override: None,
+            theme: None,
+            color: ColorLevel::TrueColor,
+            decorations: Decorations::Plain,
         },
         "fn main() {}",
     );
@@ -112,6 +146,9 @@ fn pretty_syntax_override() {
     let fmt = Pretty {
         line_numbers: false,
         syntax_override: Some("toml".to_string()),
+        theme: None,
+        color: ColorLevel::TrueColor,
+        decorations: Decorations::Plain,
     };
     let out = capture_with_path(&fmt, Path::new("foo.rs"), "key = 'value'");
 
@@ -119,6 +156,9 @@ fn pretty_syntax_override() {
     let fmt_plain = Pretty {
         line_numbers: false,
         syntax_override: None,
+        theme: None,
+        color: ColorLevel::TrueColor,
+        decorations: Decorations::Plain,
     };
     let out_plain = capture_with_path(&fmt_plain, Path::new("foo.txt"), "key = 'value'");
 
@@ -133,6 +173,9 @@ fn pretty_modeline_detection() {
     let fmt = Pretty {
         line_numbers: false,
         syntax_override: None,
+        theme: None,
+        color: ColorLevel::TrueColor,
+        decorations: Decorations::Plain,
     };
 
     // Use a .txt extension to prove modeline is being used over the file extension.
@@ -144,3 +187,50 @@ fn pretty_modeline_detection() {
     assert!(out.contains("\x1b[")); // Should be highlighted as rust.
     assert_ne!(out, out_plain); // Should be different from plain text version.
 }
+
+#[test]
+fn pretty_decorations() {
+    let fmt = |decorations| Pretty {
+        line_numbers: true,
+        syntax_override: None,
+        theme: None,
+        color: ColorLevel::None,
+        decorations,
+    };
+    let rule = |junction: char| format!("──{junction}{}\n", "─".repeat(77));
+
+    let full = capture(&fmt(Decorations::Full), "fn main() {}");
+    let expected = format!(
+        "{}  │ File: foo.rs\n  │ Size: 12 B, Language: Rust\n{}1 │ fn main() {{}}\n{}",
+        rule('┬'),
+        rule('┼'),
+        rule('┴'),
+    );
+    assert_eq!(full, expected);
+
+    let header = capture(&fmt(Decorations::Header), "fn main() {}\n");
+    assert_eq!(
+        header,
+        "File: foo.rs\nSize: 13 B, Language: Rust\n1 │ fn main() {}\n\n"
+    );
+
+    let plain = capture(&fmt(Decorations::Plain), "fn main() {}\n");
+    assert_eq!(plain, "1 │ fn main() {}\n");
+}
+
+#[test]
+fn pretty_header_is_styled_but_body_is_unchanged() {
+    let fmt = |decorations| Pretty {
+        line_numbers: false,
+        syntax_override: None,
+        theme: None,
+        color: ColorLevel::TrueColor,
+        decorations,
+    };
+    let content = "fn main() {}\n";
+    let plain = capture(&fmt(Decorations::Plain), content);
+    let header = capture(&fmt(Decorations::Header), content);
+
+    assert!(header.starts_with("File: \x1b[1mfoo.rs\x1b[0m\n"));
+    assert!(header.contains(&plain));
+}
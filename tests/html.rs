@@ -0,0 +1,200 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn run(args: &[&str], files: &[&Path]) -> String {
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "html"])
+        .args(args)
+        .args(files)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// The page is also well-formed XML, so it can be checked with an XML parser.
+fn parse(page: &str) -> roxmltree::Document<'_> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..roxmltree::ParsingOptions::default()
+    };
+    roxmltree::Document::parse_with_options(page, options).unwrap()
+}
+
+/// Text of the code block of each file section, which is the file content
+/// after the newline that HTML drops after `<pre>`.
+fn code_texts(page: &str) -> Vec<String> {
+    let doc = parse(page);
+    doc.descendants()
+        .filter(|n| n.has_tag_name("section"))
+        .map(|section| {
+            let pre = section
+                .descendants()
+                .rfind(|n| n.has_tag_name("pre"))
+                .unwrap();
+            let text: String = pre
+                .descendants()
+                .filter(roxmltree::Node::is_text)
+                .filter_map(|n| n.text())
+                .collect();
+            text.strip_prefix('\n').unwrap().to_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn index_links_to_every_section() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    let b = prepare_file(dir.path(), "b&c.txt", "text\n");
+    let page = run(&[], &[&a, &b]);
+    assert!(page.starts_with("<!DOCTYPE html>\n<html lang=\"en\">\n"));
+    let doc = parse(&page);
+    let links: Vec<&str> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("nav"))
+        .flat_map(|nav| nav.descendants().filter_map(|n| n.attribute("href")))
+        .collect();
+    let ids: Vec<String> = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("section"))
+        .map(|n| format!("#{}", n.attribute("id").unwrap()))
+        .collect();
+    assert_eq!(links, ids);
+    assert_eq!(links, ["#file-1", "#file-2"]);
+    assert!(page.contains(&format!(">{}</a></li>", b.display()).replace('&', "&amp;")));
+}
+
+#[test]
+fn contents_are_escaped_and_kept_exactly() {
+    let dir = tempdir().unwrap();
+    let bodies = [
+        "\nstarts with a blank line\n",
+        "</pre><script>alert('x')</script> & \"quotes\"",
+        "fn main() {\n    if a < b && c > d {}\n}\n",
+    ];
+    let files: Vec<PathBuf> = bodies
+        .iter()
+        .enumerate()
+        .map(|(i, body)| prepare_file(dir.path(), &format!("{i}.rs"), body))
+        .collect();
+    let paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    for style in ["inline", "classes"] {
+        let page = run(&["--html-style", style], &paths);
+        assert!(!page.contains("<script>"));
+        assert_eq!(code_texts(&page), bodies);
+    }
+}
+
+#[test]
+fn inline_and_class_styles() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+
+    let inline = run(&[], &[&file]);
+    assert!(inline.contains("<span style=\"color:#"));
+    assert!(!inline.contains("hl-"));
+
+    let classes = run(&["--html-style", "classes"], &[&file]);
+    assert!(classes.contains(".hl-code {"));
+    assert!(classes.contains("<div class=\"code hl-code\">"));
+    assert!(classes.contains("<span class=\"hl-"));
+    assert!(!classes.contains("<span style="));
+}
+
+#[test]
+fn syntax_override() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "fn main() {}\n");
+    let plain = run(&["--html-style", "classes"], &[&file]);
+    assert!(!plain.contains("hl-rust\">"));
+    let rust = run(
+        &["--html-style", "classes", "--pretty-syntax", "rust"],
+        &[&file],
+    );
+    assert!(rust.contains("hl-rust\">"));
+}
+
+#[test]
+fn line_number_gutter() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.py", "a = 1\nb = 2\nc = 3\n");
+    let numbered = run(&["-n"], &[&file]);
+    assert!(numbered.contains("<td class=\"ln\"><pre>\n1\n2\n3\n</pre></td>"));
+    assert_eq!(code_texts(&numbered), ["a = 1\nb = 2\nc = 3\n"]);
+    assert!(!run(&[], &[&file]).contains("class=\"ln\""));
+}
+
+#[test]
+fn split_parts_are_standalone_pages() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", &"alpha\n".repeat(100));
+    let b = prepare_file(dir.path(), "b.txt", &"beta\n".repeat(100));
+    let out = dir.path().join("parts");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "html", "--split", "2000", "--split-dir"])
+        .arg(&out)
+        .arg(&a)
+        .arg(&b)
+        .assert()
+        .success();
+    let second = std::fs::read_to_string(out.join("part-2.html")).unwrap();
+    parse(&second);
+    assert!(second.contains("<ol start=\"2\">"));
+    assert!(second.contains("<section id=\"file-2\">"));
+}
+
+#[test]
+fn style_from_config_and_invalid_values() {
+    let dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    };
+    prepare_file(
+        home.path(),
+        &format!("{config_dir}/config.toml"),
+        "format = \"html\"\nhtml_style = \"classes\"\n",
+    );
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home.path())
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("<span class=\"hl-"));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["x.rs", "-f", "html", "--html-style", "css"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid HTML style 'css'. Valid values are: inline, classes",
+        ));
+}
@@ -0,0 +1,180 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::json::{SCHEMA, VERSION};
+use serde_json::{Value, json};
+use tempfile::tempdir;
+
+fn schema() -> Value {
+    serde_json::from_str(SCHEMA).unwrap()
+}
+
+/// The schema for a single `-f jsonl` line, sharing the definitions of the document schema.
+fn line_schema() -> Value {
+    let root = schema();
+    json!({
+        "$schema": root["$schema"],
+        "$defs": root["$defs"],
+        "$ref": "#/$defs/line",
+    })
+}
+
+fn run(args: &[&str]) -> String {
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(args)
+        .output()
+        .unwrap();
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn assert_valid(schema: &Value, instance: &Value) {
+    let validator = jsonschema::validator_for(schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|e| e.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{instance}: {errors:?}");
+}
+
+#[test]
+fn document_matches_schema() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "src/a.rs", "fn main() {}\n");
+    let b = prepare_file(dir.path(), "b.py", "print(1)\n");
+    let missing = dir.path().join("missing.txt");
+    let out = run(&[
+        "-f",
+        "json",
+        "--tree",
+        "--meta",
+        "size,mtime,lines,lang,sha256,mode",
+        "--prompt",
+        "Review these.",
+        a.to_str().unwrap(),
+        b.to_str().unwrap(),
+        missing.to_str().unwrap(),
+    ]);
+    let doc: Value = serde_json::from_str(&out).unwrap();
+    assert_valid(&schema(), &doc);
+    assert_eq!(doc["version"], VERSION);
+    assert_eq!(doc["files"].as_array().unwrap().len(), 2);
+    assert_eq!(doc["errors"][0]["path"], missing.display().to_string());
+    assert_eq!(doc["stats"]["files"], 2);
+}
+
+#[test]
+fn split_parts_match_schema() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", &"a".repeat(400));
+    let b = prepare_file(dir.path(), "b.txt", &"b".repeat(400));
+    let out_dir = dir.path().join("parts");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "json", "--split", "150", "--split-dir"])
+        .arg(&out_dir)
+        .arg(&a)
+        .arg(&b)
+        .assert()
+        .success();
+    let mut parts = 0;
+    for entry in std::fs::read_dir(&out_dir).unwrap() {
+        let doc: Value =
+            serde_json::from_slice(&std::fs::read(entry.unwrap().path()).unwrap()).unwrap();
+        assert_valid(&schema(), &doc);
+        assert!(doc["part"].is_string());
+        parts += 1;
+    }
+    assert!(parts >= 2);
+}
+
+#[test]
+fn jsonl_lines_match_schema() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", "one\n");
+    let b = prepare_file(dir.path(), "b.txt", "two\n");
+    let missing = dir.path().join("missing.txt");
+    let out = run(&[
+        "-f",
+        "jsonl",
+        "--meta",
+        "lines",
+        a.to_str().unwrap(),
+        missing.to_str().unwrap(),
+        b.to_str().unwrap(),
+    ]);
+    let lines: Vec<Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    let schema = line_schema();
+    for line in &lines {
+        assert_valid(&schema, line);
+    }
+    let types: Vec<&str> = lines.iter().map(|l| l["type"].as_str().unwrap()).collect();
+    assert_eq!(types, ["file", "error", "file"]);
+    assert_eq!(lines[2]["content"], "two\n");
+    assert_eq!(lines[0]["lines"], 1);
+}
+
+#[test]
+fn jsonl_with_tree_and_stats_matches_schema() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "src/a.rs", "fn main() {}\n");
+    let out = run(&[
+        "-f",
+        "jsonl",
+        "--tree",
+        "--stats",
+        "footer",
+        "--prompt",
+        "Review.",
+        a.to_str().unwrap(),
+    ]);
+    let schema = line_schema();
+    let types: Vec<String> = out
+        .lines()
+        .map(|l| {
+            let line: Value = serde_json::from_str(l).unwrap();
+            assert_valid(&schema, &line);
+            line["type"].as_str().unwrap().to_owned()
+        })
+        .collect();
+    assert_eq!(types, ["prompt", "tree", "file", "stats"]);
+}
+
+#[test]
+fn schema_flag_prints_the_schema() {
+    let out = run(&["--json-schema"]);
+    let printed: Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(printed, schema());
+    jsonschema::meta::validate(&printed).unwrap();
+}
+
+#[test]
+fn jsonl_is_a_valid_format_value() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["x.txt", "-f", "jsonish"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("json, jsonl,"));
+}
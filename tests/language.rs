@@ -0,0 +1,98 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use rucat::cli::{MarkdownFence, MarkdownHeader};
+use rucat::formatters::{Formatter, markdown::Markdown};
+use rucat::language::{Language, language_name};
+use std::path::Path;
+
+fn id(path: &str, content: &str) -> Option<&'static str> {
+    Language::detect(Path::new(path), content, None).map(|l| l.id)
+}
+
+#[test]
+fn extensions_map_to_canonical_ids() {
+    assert_eq!(id("main.rs", ""), Some("rust"));
+    assert_eq!(id("app.PY", ""), Some("python"));
+    assert_eq!(id("lib.hpp", ""), Some("cpp"));
+    assert_eq!(id("notes.unknownext", ""), None);
+    // Plain text has a name but no fence tag.
+    assert_eq!(id("notes.txt", ""), Some(""));
+    assert_eq!(language_name(Path::new("notes.txt"), ""), "Plain Text");
+    assert_eq!(language_name(Path::new("run.sh"), ""), "Shell");
+}
+
+#[test]
+fn special_file_names() {
+    assert_eq!(id("Dockerfile", ""), Some("dockerfile"));
+    assert_eq!(id("docker/Dockerfile.prod", ""), Some("dockerfile"));
+    assert_eq!(id("GNUmakefile", ""), Some("makefile"));
+    assert_eq!(id("/home/me/.bashrc", ""), Some("bash"));
+    assert_eq!(id("CMakeLists.txt", ""), Some("cmake"));
+    // Only names listed with a suffix match one.
+    assert_eq!(id("Gemfile.lock", ""), None);
+    assert_eq!(id("Makefile.am", ""), None);
+}
+
+#[test]
+fn shebangs() {
+    assert_eq!(id("tool", "#!/usr/bin/env python3\n"), Some("python"));
+    assert_eq!(
+        id("tool", "#!/usr/bin/env -S node --no-warnings\n"),
+        Some("javascript")
+    );
+    assert_eq!(id("tool", "#!/bin/bash -e\n"), Some("bash"));
+    assert_eq!(id("tool", "#!/usr/local/bin/python3.12\n"), Some("python"));
+    assert_eq!(id("tool", "#!/opt/unknown\n"), None);
+}
+
+#[test]
+fn modelines_beat_shebangs_and_extensions() {
+    assert_eq!(id("a.txt", "x\n# vim: set ft=ruby:\n"), Some("ruby"));
+    assert_eq!(id("a.h", "/* -*- C++ -*- */\n"), Some("cpp"));
+    assert_eq!(
+        id(
+            "a.txt",
+            "#!/bin/sh\n# -*- mode: python; coding: utf-8 -*-\n"
+        ),
+        Some("python")
+    );
+    assert_eq!(id("a.txt", "vim: ft=rust in the middle\n"), Some("rust"));
+}
+
+#[test]
+fn override_wins() {
+    let lang = Language::detect(Path::new("a.py"), "# vim: ft=ruby\n", Some("sh")).unwrap();
+    assert_eq!(lang.id, "bash");
+    assert!(lang.syntax().is_some());
+}
+
+#[test]
+fn markdown_fence_uses_detected_language() {
+    let fence = |path: &str, content: &str| {
+        let mut buf = Vec::new();
+        Markdown {
+            line_numbers: false,
+            fence: MarkdownFence::Backticks,
+            header: MarkdownHeader::FrontMatter,
+        }
+        .write(Path::new(path), content, &mut buf)
+        .unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    assert!(fence("deploy", "#!/bin/sh\necho hi\n").contains("```bash\n"));
+    assert!(fence("notes.txt", "hi\n").contains("---\n```\nhi\n```"));
+}
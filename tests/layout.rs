@@ -0,0 +1,134 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use rucat::cli::LineWrap;
+use rucat::color::ColorLevel;
+use rucat::formatters::layout::{self, Layout};
+use rucat::formatters::{
+    Formatter,
+    ansi::{Ansi, AnsiStyle},
+    utf8::Utf8,
+};
+use std::path::Path;
+
+fn utf8(width: usize, line_numbers: bool, wrap: LineWrap) -> Utf8 {
+    Utf8 {
+        width,
+        line_numbers,
+        tabs: 4,
+        wrap,
+    }
+}
+
+fn capture<F: Formatter>(fmt: &F, content: &str) -> String {
+    let mut buf = Vec::new();
+    fmt.write(Path::new("a.txt"), content, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+/// Display widths of the output's lines.
+fn widths(out: &str) -> Vec<usize> {
+    out.lines().map(layout::width).collect()
+}
+
+#[test]
+fn measures_columns_not_bytes() {
+    assert_eq!(layout::width("abc"), 3);
+    assert_eq!(layout::width("日本"), 4);
+    assert_eq!(layout::width("é"), 1);
+    assert_eq!(layout::width("🎉"), 2);
+}
+
+#[test]
+fn expands_tabs_to_stops() {
+    assert_eq!(layout::expand_tabs("a\tb", 4), "a   b");
+    assert_eq!(layout::expand_tabs("abcd\tb", 4), "abcd    b");
+    assert_eq!(layout::expand_tabs("日\tb", 4), "日  b");
+    assert_eq!(layout::expand_tabs("a\tb", 0), "a\tb");
+}
+
+#[test]
+fn splits_and_truncates_by_width() {
+    assert_eq!(layout::split("abcdef", 4), ["abcd", "ef"]);
+    assert_eq!(layout::split("日本語", 4), ["日本", "語"]);
+    assert_eq!(layout::split("a日本", 2), ["a", "日", "本"]);
+    assert_eq!(layout::truncate("abcdef", 4), "abc…");
+    assert_eq!(layout::truncate("日本語", 4), "日…");
+    assert_eq!(layout::truncate("abcd", 4), "abcd");
+}
+
+#[test]
+fn wide_characters_keep_the_border_straight() {
+    let content = "日本語のテキスト\ncafé 🎉\nplain\n";
+    for wrap in [LineWrap::Wrap, LineWrap::Truncate, LineWrap::Never] {
+        let out = capture(&utf8(30, true, wrap), content);
+        let widths = widths(&out);
+        assert!(widths.iter().all(|&w| w == 32), "{wrap:?}: {out}");
+    }
+}
+
+#[test]
+fn long_lines_wrap_with_a_continuation_marker() {
+    let out = capture(&utf8(12, true, LineWrap::Wrap), "abcdefghijklmnopq\nx\n");
+    assert!(out.contains("│1 │ abcdefgh│\n"), "{out}");
+    assert!(out.contains("│↪ │ ijklmnop│\n"), "{out}");
+    assert!(out.contains("│↪ │ q       │\n"), "{out}");
+    assert!(out.contains("│2 │ x       │\n"), "{out}");
+    assert!(widths(&out).iter().all(|&w| w == 14), "{out}");
+}
+
+#[test]
+fn long_lines_truncate_or_widen_the_box() {
+    let out = capture(&utf8(12, false, LineWrap::Truncate), "abcdefghijklmnopq\n");
+    assert!(out.contains("│abcdefghijk…│\n"), "{out}");
+
+    let out = capture(&utf8(12, false, LineWrap::Never), "abcdefghijklmnopq\n");
+    assert!(out.contains("│abcdefghijklmnopq│\n"), "{out}");
+}
+
+#[test]
+fn long_headers_are_cut_to_the_box() {
+    let fmt = Ansi {
+        width: 10,
+        line_numbers: false,
+        tabs: 4,
+        wrap: LineWrap::Wrap,
+        color: ColorLevel::None,
+        style: AnsiStyle::default(),
+        highlight: false,
+        syntax_override: None,
+        theme: None,
+    };
+    let out = capture(&fmt, "x");
+    assert!(out.contains("│ File: a.…│"), "{out}");
+    assert!(widths(&out).iter().all(|&w| w == 12), "{out}");
+}
+
+#[test]
+fn plain_rows_keep_blank_lines() {
+    let layout = Layout {
+        width: 3,
+        tabs: 4,
+        wrap: LineWrap::Wrap,
+    };
+    let lines = ["abcd".to_string(), String::new(), "e".to_string()];
+    let rows: Vec<String> = layout
+        .plain_rows(&lines)
+        .into_iter()
+        .map(|row| row.text)
+        .collect();
+    assert_eq!(rows, ["abc", "d", "", "e"]);
+}
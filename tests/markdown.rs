@@ -14,6 +14,9 @@
 // along with rucat.  If not, see <https://www.gnu.org/licenses/>.
 //
 // Copyright (C) 2024 Brian 'redbeard' Harrington
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rucat::cli::{MarkdownFence, MarkdownHeader};
 use rucat::formatters::{Formatter, markdown::Markdown};
 use std::path::Path;
 
@@ -22,9 +25,103 @@ fn md_basic() {
     let mut buf = Vec::new();
     let fmt = Markdown {
         line_numbers: false,
+        fence: MarkdownFence::Backticks,
+        header: MarkdownHeader::FrontMatter,
     }; // instantiate struct
     fmt.write(Path::new("foo.rs"), "fn main(){}", &mut buf)
         .unwrap();
     let out = String::from_utf8(buf).unwrap();
-    assert!(out.contains("```rs"));
+    assert!(out.contains("```rust\n"));
+}
+
+fn render(fence: MarkdownFence, header: MarkdownHeader, path: &str, content: &str) -> String {
+    let mut buf = Vec::new();
+    Markdown {
+        line_numbers: false,
+        fence,
+        header,
+    }
+    .write(Path::new(path), content, &mut buf)
+    .unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn md_fence_outgrows_backtick_runs() {
+    let out = render(
+        MarkdownFence::Backticks,
+        MarkdownHeader::FrontMatter,
+        "README.md",
+        "Example:\n\n```rust\nlet a = 1;\n```\n\n````\nnested\n````\n",
+    );
+    assert!(out.starts_with("---\nFile: README.md\n---\n`````markdown\n"));
+    assert!(out.ends_with("````\n`````\n"));
+}
+
+#[test]
+fn md_tilde_fences_ignore_backticks() {
+    let out = render(
+        MarkdownFence::Tildes,
+        MarkdownHeader::FrontMatter,
+        "a.md",
+        "```\ncode\n```\n~~~~\n",
+    );
+    assert!(out.contains("\n~~~~~markdown\n```\ncode\n"));
+    assert!(out.ends_with("~~~~\n~~~~~\n"));
+}
+
+#[test]
+fn md_header_styles() {
+    let heading = render(
+        MarkdownFence::Backticks,
+        MarkdownHeader::Heading,
+        "src/a.rs",
+        "x\n",
+    );
+    assert_eq!(heading, "### src/a.rs\n```rust\nx\n```\n");
+    let bold = render(
+        MarkdownFence::Backticks,
+        MarkdownHeader::Bold,
+        "src/a.rs",
+        "x\n",
+    );
+    assert_eq!(bold, "**src/a.rs**\n```rust\nx\n```\n");
+}
+
+#[test]
+fn cli_header_from_config_and_fence_flag_after_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = tempfile::tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    };
+    std::fs::create_dir_all(home.path().join(config_dir)).unwrap();
+    std::fs::write(
+        home.path().join(config_dir).join("config.toml"),
+        "markdown_header = \"heading\"\n",
+    )
+    .unwrap();
+    std::fs::write(dir.path().join("notes.md"), "```\nx\n```\n").unwrap();
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home.path())
+        .current_dir(dir.path())
+        .args(["notes.md", "--markdown-fence", "tildes"])
+        .assert()
+        .success()
+        .stdout("### notes.md\n~~~markdown\n```\nx\n```\n~~~\n");
+}
+
+#[test]
+fn cli_rejects_unknown_header_style() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["x.md", "--markdown-header", "table"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid markdown header 'table'. Valid values are: front-matter, heading, bold",
+        ));
 }
@@ -0,0 +1,140 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::cli::{MessagesApi, MessagesContent};
+use rucat::messages::Envelope;
+use serde_json::{Value, json};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn run(args: &[&str], files: &[&Path]) -> Value {
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "messages"])
+        .args(args)
+        .args(files)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    serde_json::from_slice(&output.stdout).unwrap()
+}
+
+#[test]
+fn openai_puts_the_system_prompt_in_messages() {
+    let envelope = Envelope {
+        api: MessagesApi::Openai,
+        content: MessagesContent::Files,
+        system: Some("Be terse.".to_owned()),
+        model: Some("gpt-4o".to_owned()),
+    };
+    let body: Value =
+        serde_json::from_str(&envelope.to_json(&["a".to_owned(), "b".to_owned()]).unwrap())
+            .unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "Be terse."},
+                {"role": "user", "content": [
+                    {"type": "text", "text": "a"},
+                    {"type": "text", "text": "b"},
+                ]},
+            ],
+        })
+    );
+}
+
+#[test]
+fn anthropic_uses_top_level_system_and_max_tokens() {
+    let envelope = Envelope {
+        api: MessagesApi::Anthropic,
+        content: MessagesContent::Single,
+        system: Some("Be terse.".to_owned()),
+        model: None,
+    };
+    let body: Value =
+        serde_json::from_str(&envelope.to_json(&["a".to_owned(), "b".to_owned()]).unwrap())
+            .unwrap();
+    assert_eq!(
+        body,
+        json!({
+            "max_tokens": 4096,
+            "system": "Be terse.",
+            "messages": [
+                {"role": "user", "content": [{"type": "text", "text": "a\nb"}]},
+            ],
+        })
+    );
+}
+
+#[test]
+fn cli_one_block_per_file_after_the_prompt() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", "one\n");
+    let b = prepare_file(dir.path(), "b.txt", "two\n");
+    let body = run(&["--prompt", "Review these."], &[&a, &b]);
+    let blocks = body["messages"][0]["content"].as_array().unwrap();
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(blocks[0]["text"], "Review these.");
+    assert_eq!(blocks[2]["text"], format!("File: {}\n\ntwo\n", b.display()));
+}
+
+#[test]
+fn cli_model_and_system_from_config() {
+    let dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    };
+    prepare_file(
+        home.path(),
+        &format!("{config_dir}/config.toml"),
+        "messages_api = \"anthropic\"\nmodel = \"claude-sonnet-4-5\"\nsystem = \"You review code.\"\n",
+    );
+    let file = prepare_file(dir.path(), "a.txt", "one\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home.path())
+        .args(["-f", "messages", "--model", "local-model"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"model\": \"local-model\"")
+                .and(predicate::str::contains("\"system\": \"You review code.\""))
+                .and(predicate::str::contains("\"max_tokens\"")),
+        );
+}
+
+#[test]
+fn cli_rejects_unknown_api() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "messages", "x.txt", "--messages-api", "gemini"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid messages API 'gemini'. Valid values are: openai, anthropic",
+        ));
+}
@@ -0,0 +1,137 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::cli::MetaField;
+use rucat::meta::Metadata;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn collects_only_the_requested_fields() {
+    let meta = Metadata::collect(
+        &[MetaField::Lines, MetaField::Sha256],
+        Path::new("main.rs"),
+        "abc",
+        None,
+    );
+    assert_eq!(meta.lines, Some(1));
+    assert_eq!(
+        meta.sha256.as_deref(),
+        Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    );
+    assert_eq!(meta.size, None);
+    assert_eq!(meta.lang, None);
+}
+
+#[test]
+fn summary_follows_the_path() {
+    let meta = Metadata::collect(
+        &[MetaField::Lines, MetaField::Lang, MetaField::Mtime],
+        Path::new("src/main.rs"),
+        "fn main() {}\n\n",
+        None,
+    );
+    assert_eq!(
+        meta.label(Path::new("src/main.rs")),
+        "src/main.rs (2 lines, Rust)"
+    );
+    assert_eq!(Metadata::default().label(Path::new("a.txt")), "a.txt");
+}
+
+#[test]
+fn cli_annotates_headers() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/main.rs", "fn main() {}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "markdown", "--meta", "lines,size", "src/main.rs"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "---\nFile: src/main.rs (13 bytes, 1 line)\n---\n",
+        ));
+}
+
+#[test]
+fn cli_json_fields_and_xml_attributes() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.py", "print(1)\nprint(2)\n");
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "json", "--meta", "lines,lang,mtime"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["files"][0]["lines"], 2);
+    assert_eq!(json["files"][0]["lang"], "Python");
+    assert!(json["files"][0]["mtime"].as_str().unwrap().ends_with('Z'));
+    assert!(json["files"][0].get("sha256").is_none());
+
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "xml", "--meta", "lang,lines"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("lines=\"2\" lang=\"Python\">"));
+}
+
+#[cfg(unix)]
+#[test]
+fn cli_mode_from_config() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempdir().unwrap();
+    let home = tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    };
+    prepare_file(
+        home.path(),
+        &format!("{config_dir}/config.toml"),
+        "meta = [\"mode\"]\n",
+    );
+    let file = prepare_file(dir.path(), "run.sh", "echo hi\n");
+    std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o750)).unwrap();
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home.path())
+        .args(["-f", "ascii"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(mode 0750) ==="));
+}
+
+#[test]
+fn cli_rejects_unknown_field() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["x.txt", "--meta", "lines,owner"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Invalid metadata field 'owner'. Valid values are: size, mtime, lines, lang, sha256, mode",
+        ));
+}
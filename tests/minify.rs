@@ -0,0 +1,94 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rucat::minify::Minified;
+use std::path::Path;
+use tempfile::tempdir;
+
+const SOURCE: &str = "// Copyright (C) 2024 Example Corp
+// Licensed under the MIT license.
+
+/// Adds two numbers.
+fn add(a: i32, b: i32) -> i32 {
+    // plain comment
+    let url = \"http://example.com\"; // trailing
+    /* block
+       comment */
+    a + b
+}
+";
+
+#[test]
+fn strips_comments_but_not_strings_or_docs() {
+    let m = Minified::new(SOURCE).strip_comments(Path::new("a.rs"), true);
+    assert_eq!(
+        m.content,
+        "/// Adds two numbers.\nfn add(a: i32, b: i32) -> i32 {\n    let url = \"http://example.com\";\n    a + b\n}\n"
+    );
+    assert_eq!(m.lines, vec![4, 5, 7, 10, 11]);
+}
+
+#[test]
+fn strips_doc_comments_on_request() {
+    let m = Minified::new(SOURCE).strip_comments(Path::new("a.rs"), false);
+    assert!(!m.content.contains("Adds two numbers"));
+    assert!(m.content.starts_with("fn add"));
+}
+
+#[test]
+fn keeps_shebang_and_unknown_languages() {
+    let script = "#!/bin/sh\n# comment\necho '# not a comment'\n";
+    let m = Minified::new(script).strip_comments(Path::new("run.sh"), true);
+    assert_eq!(m.content, "#!/bin/sh\necho '# not a comment'\n");
+
+    let text = "// not code\n";
+    let m = Minified::new(text).strip_comments(Path::new("notes.unknown"), true);
+    assert_eq!(m.content, text);
+}
+
+#[test]
+fn minifies_whitespace() {
+    let m = Minified::new("\n\na  \n\n\n\nb\t\n\n").minify_whitespace();
+    assert_eq!(m.content, "a\n\nb\n");
+    assert_eq!(m.lines, vec![3, 4, 7]);
+}
+
+#[test]
+fn cli_line_numbers_point_at_original_lines() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("a.rs");
+    std::fs::write(&file, SOURCE).unwrap();
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args([
+            "-f",
+            "ascii",
+            "-n",
+            "--strip-comments",
+            "--minify-whitespace",
+        ])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains(" 5 | fn add")
+                .and(predicate::str::contains("10 |     a + b"))
+                .and(predicate::str::contains("plain comment").not())
+                .and(predicate::str::contains("Copyright").not()),
+        );
+}
@@ -0,0 +1,221 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::json::FileError;
+use rucat::output::{Index, Mirror, write_atomic};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+fn rucat(dir: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("rucat").unwrap();
+    cmd.current_dir(dir).env("HOME", dir);
+    cmd
+}
+
+#[test]
+fn write_atomic_replaces_the_file_and_leaves_nothing_behind() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("bundle.md");
+    fs::write(&path, "old").unwrap();
+    write_atomic(&path, b"new").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    let missing = dir.path().join("no/such/dir/bundle.md");
+    assert!(write_atomic(&missing, b"new").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn write_atomic_keeps_the_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("run.sh");
+    fs::write(&path, "old").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+    write_atomic(&path, b"new").unwrap();
+    let mode = fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o750);
+}
+
+#[test]
+fn mirror_keeps_paths_inside_the_directory() {
+    let mut mirror = Mirror::new();
+    assert_eq!(
+        mirror.path(Path::new("src/lib.rs"), "md"),
+        Path::new("src/lib.rs.md")
+    );
+    assert_eq!(
+        mirror.path(Path::new("../other/src/lib.rs"), "md"),
+        Path::new("other/src/lib.rs.md")
+    );
+    assert_eq!(
+        mirror.path(Path::new("/etc/hosts"), "txt"),
+        Path::new("etc/hosts.txt")
+    );
+    assert_eq!(mirror.path(Path::new("-"), "json"), Path::new("stdin.json"));
+    // Taken paths, and the index itself, get a number.
+    assert_eq!(
+        mirror.path(Path::new("./src/lib.rs"), "md"),
+        Path::new("src/lib.rs.2.md")
+    );
+    assert_eq!(
+        mirror.path(Path::new("index"), "md"),
+        Path::new("index.2.md")
+    );
+}
+
+#[test]
+fn index_links_each_file() {
+    let errors = [FileError {
+        path: "gone.rs".to_owned(),
+        error: "not found".to_owned(),
+    }];
+    let index = Index {
+        prompt: Some("Review these."),
+        files: vec![
+            (Path::new("src/a.rs"), PathBuf::from("src/a.rs.md")),
+            (Path::new("my notes.txt"), PathBuf::from("my notes.txt.md")),
+        ],
+        errors: &errors,
+        note: Some("budget reached"),
+        ..Index::default()
+    };
+    assert_eq!(
+        index.to_markdown(),
+        "# Index\n\nReview these.\n\n\
+         - [src/a.rs](src/a.rs.md)\n\
+         - [my notes.txt](<my notes.txt.md>)\n\n\
+         ## Errors\n\n- gone.rs: not found\n\n\
+         > budget reached\n"
+    );
+}
+
+#[test]
+fn output_writes_the_file_instead_of_stdout() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a.txt", "hello");
+    rucat(dir.path())
+        .args(["-f", "ascii", "--output", "bundle.txt", "a.txt"])
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("bundle.txt")).unwrap(),
+        "=== a.txt ===\nhello\n"
+    );
+}
+
+#[test]
+fn tee_prints_what_it_writes() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a.txt", "hello");
+    let out = rucat(dir.path())
+        .args(["a.txt", "-o", "bundle.md", "--tee"])
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let written = fs::read(dir.path().join("bundle.md")).unwrap();
+    assert_eq!(out.stdout, written);
+    assert!(String::from_utf8(written).unwrap().contains("hello"));
+}
+
+#[test]
+fn failed_run_keeps_the_old_output() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a.txt", "hello");
+    fs::write(dir.path().join("bundle.md"), "old").unwrap();
+    rucat(dir.path())
+        .args(["--split", "1", "-o", "bundle.md", "a.txt"])
+        .assert()
+        .failure();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("bundle.md")).unwrap(),
+        "old"
+    );
+}
+
+#[test]
+fn output_dir_mirrors_paths_and_writes_an_index() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/a.rs", "fn a() {}\n");
+    prepare_file(dir.path(), "b.toml", "x = 1\n");
+    rucat(dir.path())
+        .args(["--output-dir", "out", "src/a.rs", "b.toml", "missing.rs"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Wrote 2 file(s) to out"));
+
+    let out = dir.path().join("out");
+    assert_eq!(
+        fs::read_to_string(out.join("src/a.rs.md")).unwrap(),
+        "---\nFile: src/a.rs\n---\n```rust\nfn a() {}\n```\n"
+    );
+    assert!(
+        fs::read_to_string(out.join("b.toml.md"))
+            .unwrap()
+            .contains("x = 1")
+    );
+    let index = fs::read_to_string(out.join("index.md")).unwrap();
+    assert!(index.contains("- [src/a.rs](src/a.rs.md)\n- [b.toml](b.toml.md)\n"));
+    assert!(index.contains("- missing.rs:"));
+}
+
+#[test]
+fn output_dir_uses_the_format_extension() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a.txt", "hello");
+    rucat(dir.path())
+        .args(["-f", "json", "--output-dir", "out", "a.txt"])
+        .assert()
+        .success();
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(dir.path().join("out/a.txt.json")).unwrap())
+            .unwrap();
+    assert_eq!(json["files"][0]["content"], "hello");
+}
+
+#[test]
+fn rejects_conflicting_output_flags() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a.txt", "hello");
+    for (args, message) in [
+        (&["--tee"][..], "--tee needs --output"),
+        (
+            &["-o", "x.md", "--output-dir", "out"],
+            "cannot be used together",
+        ),
+        (&["--output-dir", "out", "--split", "10k"], "--split"),
+        (
+            &["-o", "x.md", "--split", "10k", "--split-dir", "p"],
+            "--split-dir",
+        ),
+    ] {
+        rucat(dir.path())
+            .args(args)
+            .arg("a.txt")
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+}
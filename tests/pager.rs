@@ -0,0 +1,143 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use rucat::pager::{self, Paged};
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn command(vars: &[(&str, &str)]) -> Option<Vec<String>> {
+    pager::command(|name| {
+        vars.iter()
+            .find(|(k, _)| *k == name)
+            .map(|(_, v)| (*v).to_string())
+    })
+}
+
+#[test]
+fn picks_the_pager_from_the_environment() {
+    assert_eq!(command(&[]).unwrap(), ["less", "-R"]);
+    assert_eq!(command(&[("PAGER", "more")]).unwrap(), ["more"]);
+    assert_eq!(
+        command(&[("PAGER", "more"), ("RUCAT_PAGER", "less -S")]).unwrap(),
+        ["less", "-S"]
+    );
+    assert_eq!(
+        command(&[("PAGER", "/usr/bin/less")]).unwrap(),
+        ["/usr/bin/less", "-R"]
+    );
+    assert_eq!(command(&[("RUCAT_PAGER", "")]), None);
+}
+
+#[test]
+fn short_output_is_not_paged() {
+    let mut out = Vec::new();
+    let mut paged = Paged::new(&mut out, vec!["false".to_string()], 5);
+    writeln!(paged, "one\ntwo").unwrap();
+    assert!(!paged.is_paging());
+    paged.finish().unwrap();
+    assert_eq!(out, b"one\ntwo\n");
+}
+
+fn rucat(home: &Path, pager: &str) -> Command {
+    let mut cmd = Command::cargo_bin("rucat").unwrap();
+    cmd.env("HOME", home)
+        .env("RUCAT_PAGER", pager)
+        .args(["-f", "ascii"]);
+    cmd
+}
+
+#[test]
+fn paging_always_goes_through_the_pager() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    rucat(dir.path(), "sed s/^/paged:/")
+        .args(["--paging", "always"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(format!("paged:=== {} ===\npaged:hello\n", file.display()));
+}
+
+#[test]
+fn pipes_and_paging_never_are_not_paged() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    let expected = format!("=== {} ===\nhello\n", file.display());
+    // stdout is a pipe here, so `auto` does not page.
+    rucat(dir.path(), "sed s/^/paged:/")
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(expected.clone());
+    rucat(dir.path(), "sed s/^/paged:/")
+        .args(["--paging", "never"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(expected);
+}
+
+#[test]
+fn a_missing_pager_falls_back_to_stdout() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    rucat(dir.path(), "no-such-pager-for-rucat")
+        .args(["--paging", "always"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("hello"))
+        .stderr(predicates::str::contains("cannot run pager"));
+}
+
+#[test]
+fn quitting_the_pager_early_is_not_an_error() {
+    let dir = tempdir().unwrap();
+    let body: String = (0..100_000).map(|n| format!("{n}\n")).collect();
+    let file = prepare_file(dir.path(), "a.txt", &body);
+    rucat(dir.path(), "head -n 1")
+        .args(["--paging", "always"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(format!("=== {} ===\n", file.display()));
+}
+
+#[cfg(unix)]
+#[test]
+fn reports_wait_for_the_pager() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    // A slow pager that shows the page on stderr, where the report goes.
+    let pager = prepare_file(dir.path(), "pager", "#!/bin/sh\nsleep 1\ncat >&2\n");
+    std::fs::set_permissions(&pager, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let output = rucat(dir.path(), pager.to_str().unwrap())
+        .args(["--paging", "always", "--stats", "stderr"])
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{output:?}");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let page = stderr.find("hello").unwrap();
+    let stats = stderr.find("Files: 1").unwrap();
+    assert!(page < stats, "{stderr}");
+}
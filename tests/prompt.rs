@@ -0,0 +1,116 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::prompt::Prompt;
+use tempfile::tempdir;
+
+/// A HOME whose config directory holds a `prompts/review.md`.
+fn home_with_library() -> tempfile::TempDir {
+    let home = tempdir().unwrap();
+    let config_dir = if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    };
+    prepare_file(
+        home.path(),
+        &format!("{config_dir}/prompts/review.md"),
+        "You are reviewing the following code.\n{{files}}\nList bugs & risks.\n",
+    );
+    home
+}
+
+#[test]
+fn splits_at_files_marker() {
+    let p = Prompt::parse("Before\n\n{{files}}\nAfter\n");
+    assert_eq!(p.before.as_deref(), Some("Before"));
+    assert_eq!(p.after.as_deref(), Some("After"));
+
+    let p = Prompt::parse("Only a preamble");
+    assert_eq!(p.before.as_deref(), Some("Only a preamble"));
+    assert_eq!(p.after, None);
+}
+
+#[test]
+fn cli_literal_prompt_precedes_markdown() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "markdown", "--prompt", "Explain this file."])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Explain this file.\n\n---\n"));
+}
+
+#[test]
+fn cli_named_prompt_wraps_xml_in_instructions() {
+    let dir = tempdir().unwrap();
+    let home = home_with_library();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home.path())
+        .args(["-f", "xml", "--prompt", "review"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::starts_with(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<files>\n\
+                 <instructions>You are reviewing the following code.</instructions>\n",
+            )
+            .and(predicate::str::ends_with(
+                "<instructions>List bugs &amp; risks.</instructions>\n</files>\n",
+            )),
+        );
+}
+
+#[test]
+fn cli_prompt_file_becomes_json_fields() {
+    let dir = tempdir().unwrap();
+    let prompt = prepare_file(dir.path(), "p.txt", "Summarise.\n{{files}}\nBe brief.\n");
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "json", "--prompt-file"])
+        .arg(&prompt)
+        .arg(&file)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["prompt"], "Summarise.");
+    assert_eq!(json["prompt_after"], "Be brief.");
+    assert_eq!(json["files"][0]["content"], "hello\n");
+}
+
+#[test]
+fn cli_missing_prompt_file_fails() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--prompt-file", "/nonexistent/prompt.txt"])
+        .write_stdin("x")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot read prompt"));
+}
@@ -0,0 +1,150 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::rewrite::{RewriteRule, Rewriter};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn parses_sed_style_rules() {
+    let rule = RewriteRule::parse("s|/home/[^/]+|~|").unwrap();
+    assert_eq!(rule.pattern, "/home/[^/]+");
+    assert_eq!(rule.replacement, "~");
+    assert!(RewriteRule::parse("s/only-pattern").is_err());
+    assert!(RewriteRule::parse("x/a/b/").is_err());
+}
+
+#[test]
+fn escaped_delimiters_are_literal() {
+    let rule = RewriteRule::parse(r"s/\/home\/me/~/").unwrap();
+    assert_eq!(rule.pattern, "/home/me");
+    assert_eq!(rule.replacement, "~");
+    let rewriter = Rewriter::new(&[rule]).unwrap();
+    assert_eq!(
+        rewriter.apply(Path::new("a"), "cd /home/me/src"),
+        "cd ~/src"
+    );
+
+    // A delimiter that means something in a regex is matched literally, and
+    // other escapes are kept.
+    let rule = RewriteRule::parse(r"s|a\|\d|x\|y|").unwrap();
+    assert_eq!(rule.pattern, r"a\|\d");
+    assert_eq!(rule.replacement, "x|y");
+}
+
+#[test]
+fn path_globs_limit_rules_and_counts_accumulate() {
+    let rules = [RewriteRule {
+        pattern: "acme".to_owned(),
+        replacement: "customer".to_owned(),
+        path: Some("*.md".to_owned()),
+    }];
+    let rewriter = Rewriter::new(&rules).unwrap();
+    assert_eq!(
+        rewriter.apply(Path::new("a.md"), "acme acme"),
+        "customer customer"
+    );
+    assert_eq!(rewriter.apply(Path::new("a.rs"), "acme"), "acme");
+    assert_eq!(rewriter.counts().collect::<Vec<_>>(), vec![("acme", 2)]);
+}
+
+#[test]
+fn cli_rewrites_bodies_and_paths() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "acme/notes.txt", "mail bob@acme.com\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            "-f",
+            "ascii",
+            "acme/notes.txt",
+            "--rewrite",
+            "s/acme/example/",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("=== example/notes.txt ===")
+                .and(predicate::str::contains("bob@example.com")),
+        )
+        .stderr(predicate::str::contains("Rewrote 2 occurrence(s)"));
+}
+
+#[test]
+fn cli_rewrites_paths_of_missing_files() {
+    let dir = tempdir().unwrap();
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args([
+            "-f",
+            "json",
+            "acme/gone.txt",
+            "--rewrite",
+            "s/acme/example/",
+        ])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["errors"][0]["path"], "example/gone.txt");
+}
+
+#[test]
+fn cli_config_rules_run_before_flags() {
+    let dir = tempdir().unwrap();
+    let home_dir = tempdir().unwrap();
+    let mut config_dir = home_dir.path().to_path_buf();
+    if cfg!(target_os = "macos") {
+        config_dir.push("Library/Application Support/rucat");
+    } else {
+        config_dir.push(".config/rucat");
+    }
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let mut config_file = File::create(config_dir.join("config.toml")).unwrap();
+    write!(
+        config_file,
+        "[[rewrite]]\npattern = 'internal\\.corp'\nreplacement = 'HOST'\n"
+    )
+    .unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "db.internal.corp\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home_dir.path())
+        .args(["-f", "ascii", "--rewrite", "s/HOST/host/"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("db.host"));
+}
+
+#[test]
+fn cli_invalid_rewrite_fails() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--rewrite", "s/(/x/"])
+        .write_stdin("x")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid rewrite pattern"));
+}
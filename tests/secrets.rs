@@ -0,0 +1,152 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::secrets::{Scanner, redact};
+use tempfile::tempdir;
+
+// Fake credentials are assembled at runtime so this file does not trip
+// secret scanners itself.
+fn aws_key() -> String {
+    format!("AKIA{}", "IOSFODNN7EXAMPLE")
+}
+
+fn private_key() -> String {
+    format!(
+        "-----BEGIN {0}-----\nMIIEvQIBADANBgkqhkiG9w0BAQEFAASC\nbm90IGEgcmVhbCBrZXk=\n-----END {0}-----\n",
+        "RSA PRIVATE KEY"
+    )
+}
+
+#[test]
+fn finds_known_credential_formats() {
+    let content = format!(
+        "key = \"{}\"\n{}token: ghp_{}\n",
+        aws_key(),
+        private_key(),
+        "a1B2c3D4e5F6g7H8i9J0k1L2m3N4o5P6q7R8"
+    );
+    let kinds: Vec<&str> = Scanner::default()
+        .scan(&content)
+        .iter()
+        .map(|f| f.kind)
+        .collect();
+    assert_eq!(kinds, vec!["aws-key", "private-key", "github-token"]);
+}
+
+#[test]
+fn redaction_keeps_line_numbers() {
+    let content = format!("before\n{}after\n", private_key());
+    let findings = Scanner::default().scan(&content);
+    let redacted = redact(&content, &findings);
+    assert!(redacted.contains("[REDACTED:private-key]"));
+    assert_eq!(redacted.lines().count(), content.lines().count());
+    assert_eq!(redacted.lines().last(), Some("after"));
+}
+
+#[test]
+fn ordinary_code_is_left_alone() {
+    let content = "let token = self.token_field;\npassword = \"changeme\"\nfn main() {}\n";
+    assert!(Scanner::default().scan(content).is_empty());
+}
+
+#[test]
+fn digests_are_not_high_entropy_secrets() {
+    let content = format!(
+        "\"integrity\": \"sha512-{}\",\nchecksum = \"{}\"\n",
+        "9LXs0i5fbvCvdgzcBTqMGc+C6RT3o2FYd6oWeGn8VqH/B1eVcHVhW2HBtAqNiCqH4QZ8kuo6SXHZFRfaKunGuQ==",
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert!(Scanner::default().scan(&content).is_empty());
+}
+
+#[test]
+fn cli_warns_by_default() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), ".env", &format!("AWS_KEY={}\n", aws_key()));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(aws_key()))
+        .stderr(
+            predicate::str::contains("Warning: 1 possible secret(s) left in the output")
+                .and(predicate::str::contains(".env:1 aws-key")),
+        );
+}
+
+#[test]
+fn cli_redact_mode_replaces_and_reports() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), ".env", &format!("AWS_KEY={}\n", aws_key()));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--secrets", "redact"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("AWS_KEY=[REDACTED:aws-key]")
+                .and(predicate::str::contains(aws_key()).not()),
+        )
+        .stderr(
+            predicate::str::contains("Redacted 1 possible secret(s)")
+                .and(predicate::str::contains(".env:1 aws-key")),
+        );
+}
+
+#[test]
+fn cli_refuse_and_off_modes() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", &aws_key());
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg(&file)
+        .args(["--secrets", "refuse"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "refusing to print 1 possible secret(s)",
+        ));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg(&file)
+        .args(["--secrets", "off"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(aws_key()));
+}
+
+#[test]
+fn cli_allowlist_suppresses_findings() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", &aws_key());
+    let allow = prepare_file(dir.path(), "allow", "# documentation key\n.*EXAMPLE$\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--secrets", "refuse", "--secrets-allowlist"])
+        .arg(&allow)
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(aws_key()));
+}
@@ -0,0 +1,157 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::split::{SplitLimit, split_lines};
+use tempfile::tempdir;
+
+/// Ten functions of five lines each, separated by blank lines.
+fn functions() -> String {
+    (1..=10)
+        .map(|n| format!("fn f{n}() {{\n    let a = {n};\n    let b = a * 2;\n    b\n}}\n"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn parses_token_and_byte_limits() {
+    assert_eq!(SplitLimit::parse("30k"), Ok(SplitLimit::Tokens(30_000)));
+    assert_eq!(SplitLimit::parse("60kb"), Ok(SplitLimit::Bytes(60_000)));
+    assert_eq!(SplitLimit::parse("512B"), Ok(SplitLimit::Bytes(512)));
+    assert!(SplitLimit::parse("lots").is_err());
+    assert!(SplitLimit::parse("xb").is_err());
+}
+
+#[test]
+fn splits_lines_after_blank_lines() {
+    let text = functions();
+    let lines: Vec<&str> = text.lines().collect();
+    let ranges = split_lines(&lines, |r| r.len() <= 20);
+    assert!(ranges.len() > 1);
+    for r in &ranges[..ranges.len() - 1] {
+        assert!(r.len() <= 20);
+        assert_eq!(
+            lines[r.end - 1],
+            "",
+            "range {r:?} should end after a blank line"
+        );
+    }
+    assert_eq!(ranges.last().unwrap().end, lines.len());
+}
+
+#[test]
+fn oversized_lines_get_a_range_each() {
+    let lines = ["a", "bbbbbbbbbb", "c"];
+    let ranges = split_lines(&lines, |r| lines[r].concat().len() <= 3);
+    assert_eq!(ranges, vec![0..1, 1..2, 2..3]);
+}
+
+#[test]
+fn cli_writes_numbered_parts_to_a_directory() {
+    let dir = tempdir().unwrap();
+    for name in ["a", "b", "c"] {
+        prepare_file(dir.path(), &format!("{name}.txt"), &"x".repeat(300));
+    }
+    let out = dir.path().join("parts");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["a.txt", "b.txt", "c.txt", "--split", "500b", "--split-dir"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Wrote 3 part(s)"));
+    let part2 = std::fs::read_to_string(out.join("part-2.md")).unwrap();
+    assert!(part2.starts_with("> **rucat:** Part 2/3\n"));
+    assert!(part2.contains("File: b.txt"));
+    assert!(part2.len() <= 500);
+}
+
+#[test]
+fn cli_json_parts_are_valid_documents() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "lib.rs", &functions());
+    let out = dir.path().join("parts");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "json", "--split", "200", "--split-dir"])
+        .arg(&out)
+        .arg(&file)
+        .assert()
+        .success();
+    let mut parts: Vec<_> = std::fs::read_dir(&out)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    parts.sort();
+    assert!(parts.len() > 1);
+    let mut joined = String::new();
+    for (idx, path) in parts.iter().enumerate() {
+        assert_eq!(path.extension().unwrap(), "json");
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(json["part"], format!("{}/{}", idx + 1, parts.len()));
+        joined.push_str(json["files"][0]["content"].as_str().unwrap());
+    }
+    assert_eq!(joined, functions());
+}
+
+#[test]
+fn cli_split_files_keep_original_line_numbers() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "lib.rs", &functions());
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "ascii", "-n", "--split", "150"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("[rucat] Part 2/")
+                .and(predicate::str::contains("59 | }"))
+                .and(predicate::str::contains(" 1 | fn f1() {")),
+        );
+}
+
+#[cfg(feature = "clipboard")]
+#[test]
+fn cli_copies_parts_one_at_a_time() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", &"a".repeat(300));
+    let b = prepare_file(dir.path(), "b.txt", &"b".repeat(300));
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--split", "500b", "--copy"])
+        .args(["--clipboard-provider-for-test", "osc52"])
+        .arg(&a)
+        .arg(&b)
+        .write_stdin("\n")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.matches("\x1b]52;c;").count(), 2);
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("Part 1/2 copied to the clipboard")
+    );
+}
@@ -0,0 +1,121 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::stats::Stats;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn collect_groups_by_language_and_ranks_largest() {
+    let files = [
+        (Path::new("a.rs"), "fn a() {}\nfn b() {}\n"),
+        (Path::new("b.rs"), "fn c() {}\n"),
+        (Path::new("c.md"), "# Title\n"),
+    ];
+    let stats = Stats::collect(files, 1);
+    assert_eq!(stats.files, 3);
+    assert_eq!(stats.skipped, 1);
+    assert_eq!(stats.lines, 4);
+    assert_eq!(stats.languages[0].language, "Rust");
+    assert_eq!(stats.languages[0].files, 2);
+    assert_eq!(stats.largest[0].path, "a.rs");
+}
+
+#[test]
+fn cli_stats_on_stderr_counts_unreadable_files() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--stats", "stderr"])
+        .arg(&file)
+        .arg(dir.path().join("missing.rs"))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Files:").not())
+        .stderr(
+            predicate::str::contains("Files: 1 (1 skipped)").and(predicate::str::contains("Rust")),
+        );
+}
+
+#[test]
+fn cli_bare_stats_goes_to_stderr() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    for args in [&["--stats"][..], &["--stats", "--"]] {
+        Command::cargo_bin("rucat")
+            .unwrap()
+            .args(["-f", "ascii"])
+            .args(args)
+            .arg(&file)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("fn main() {}"))
+            .stderr(predicate::str::contains("Files: 1 (0 skipped)"));
+    }
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg(&file)
+        .arg("--stats")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Files: 1 (0 skipped)"));
+}
+
+#[test]
+fn cli_stats_footer_follows_format() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.rs", "fn main() {}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "markdown", "--stats", "footer"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "**Summary:** 1 files (0 skipped), 1 lines, 13 bytes",
+        ));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "json", "--stats", "footer"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"stats\": {").and(predicate::str::contains("\"files\": [")),
+        );
+}
+
+#[test]
+fn cli_stats_json_is_parseable() {
+    let out = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["--stats", "json"])
+        .write_stdin("one\ntwo\n")
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let value: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(value["files"], 1);
+    assert_eq!(value["lines"], 2);
+}
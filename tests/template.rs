@@ -0,0 +1,149 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::formatters::{FileMeta, Formatter, template::Template};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn render(template: &Template, files: &[(&str, &str)]) -> String {
+    let mut buf = Vec::new();
+    template.begin_document(&mut buf).unwrap();
+    for (idx, (path, content)) in files.iter().enumerate() {
+        let meta = FileMeta {
+            index: idx + 1,
+            ..FileMeta::default()
+        };
+        template
+            .write_file(Path::new(path), content, &meta, &mut buf)
+            .unwrap();
+    }
+    template.end_document(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn document_and_file_sections() {
+    let t = Template::parse(
+        "<bundle>\n{{#files}}\n{{index}}: {{stripped_path}} [{{ext}}, {{lang}}, {{line_count}}]\n{{content}}{{/files}}\n</bundle>\n",
+    )
+    .unwrap();
+    assert_eq!(
+        render(&t, &[("a.rs", "fn a() {}\n"), ("b.py", "x = 1")]),
+        "<bundle>\n1: a.rs [rs, Rust, 1]\nfn a() {}\n2: b.py [py, Python, 1]\nx = 1\n</bundle>\n"
+    );
+}
+
+#[test]
+fn template_without_sections_repeats_per_file() {
+    let t = Template::parse("== {{path}} ==\n{{numbered_content}}").unwrap();
+    assert_eq!(
+        render(&t, &[("a.txt", "one\ntwo\n")]),
+        "== a.txt ==\n1 | one\n2 | two\n"
+    );
+}
+
+#[test]
+fn rejects_bad_templates() {
+    assert!(
+        Template::parse("{{nope}}")
+            .unwrap_err()
+            .contains("Unknown template variable 'nope'")
+    );
+    assert!(Template::parse("{{#files}}{{path}}").is_err());
+    assert!(Template::parse("{{path}}{{#files}}x{{/files}}").is_err());
+    assert!(Template::parse("{{path").is_err());
+}
+
+#[test]
+fn cli_template_flag_selects_format() {
+    let dir = tempdir().unwrap();
+    let template = prepare_file(
+        dir.path(),
+        "house.tmpl",
+        "{{#files}}\n### {{stripped_path}} (from {{path}})\n{{content}}{{/files}}\n",
+    );
+    prepare_file(dir.path(), "src/main.rs", "fn main() {}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["--strip", "1", "src/main.rs", "--template"])
+        .arg(&template)
+        .assert()
+        .success()
+        .stdout("### main.rs (from src/main.rs)\nfn main() {}\n");
+}
+
+#[test]
+fn cli_path_variable_is_rewritten() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "secret-host/main.rs", "fn main() {}\n");
+    let template = prepare_file(dir.path(), "t.txt", "{{path}}\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg("--template")
+        .arg(&template)
+        .args(["--rewrite", "s/.*secret-host/HOST/"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout("HOST/main.rs\n")
+        .stderr(predicate::str::contains("Rewrote 1 occurrence(s)"));
+}
+
+#[test]
+fn cli_template_from_config() {
+    let dir = tempdir().unwrap();
+    let home_dir = tempdir().unwrap();
+    let mut config_dir = home_dir.path().to_path_buf();
+    if cfg!(target_os = "macos") {
+        config_dir.push("Library/Application Support/rucat");
+    } else {
+        config_dir.push(".config/rucat");
+    }
+    std::fs::create_dir_all(&config_dir).unwrap();
+    let mut config_file = File::create(config_dir.join("config.toml")).unwrap();
+    write!(
+        config_file,
+        "format = \"template\"\ntemplate = \"\"\"\n@@ {{{{path}}}}\n{{{{content}}}}\"\"\"\n"
+    )
+    .unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "hello\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .env("HOME", home_dir.path())
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(format!("@@ {}\nhello\n", file.display()));
+}
+
+#[test]
+fn cli_template_format_needs_a_template() {
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", "template"])
+        .write_stdin("x")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs --template FILE"));
+}
@@ -0,0 +1,246 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use tempfile::{TempDir, tempdir};
+
+const fn config_dir() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Library/Application Support/rucat"
+    } else {
+        ".config/rucat"
+    }
+}
+
+const fn cache_dir() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Library/Caches/rucat"
+    } else {
+        ".cache/rucat"
+    }
+}
+
+/// rucat with `home` as the home directory, 24-bit colours and no hint
+/// about the terminal background.
+fn rucat(home: &TempDir) -> Command {
+    let mut cmd = Command::cargo_bin("rucat").unwrap();
+    cmd.env("HOME", home.path())
+        .env("COLORTERM", "truecolor")
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_CACHE_HOME")
+        .env_remove("COLORFGBG")
+        .args(["--color", "always"]);
+    cmd
+}
+
+fn stdout(cmd: &mut Command) -> String {
+    let output = cmd.output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// A theme that colours keywords `keyword` (`#rrggbb`) on white.
+fn theme(keyword: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+  <key>name</key>
+  <string>Plain</string>
+  <key>settings</key>
+  <array>
+    <dict>
+      <key>settings</key>
+      <dict>
+        <key>background</key>
+        <string>#FFFFFF</string>
+        <key>foreground</key>
+        <string>#000000</string>
+      </dict>
+    </dict>
+    <dict>
+      <key>scope</key>
+      <string>keyword</string>
+      <key>settings</key>
+      <dict>
+        <key>foreground</key>
+        <string>{keyword}</string>
+      </dict>
+    </dict>
+  </array>
+</dict>
+</plist>
+"#
+    )
+}
+
+const NIX_SYNTAX: &str = r"%YAML 1.2
+---
+name: Nix
+file_extensions: [nix]
+scope: source.nix
+contexts:
+  main:
+    - match: '\b(let|in|with|rec)\b'
+      scope: keyword.other.nix
+";
+
+#[test]
+fn lists_bundled_themes_and_syntaxes() {
+    let home = tempdir().unwrap();
+    let themes = stdout(rucat(&home).arg("--list-themes"));
+    assert!(themes.lines().any(|l| l == "base16-ocean.dark"));
+    assert!(themes.lines().any(|l| l == "InspiredGitHub"));
+    let syntaxes = stdout(rucat(&home).arg("--list-syntaxes"));
+    assert!(syntaxes.lines().any(|l| l == "Rust: rs"));
+}
+
+#[test]
+fn rejects_unknown_themes() {
+    let home = tempdir().unwrap();
+    rucat(&home)
+        .args(["-f", "pretty", "--theme", "nope", "x.rs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown theme 'nope' (see --list-themes)",
+        ));
+}
+
+#[test]
+fn auto_theme_follows_the_terminal_background() {
+    let home = tempdir().unwrap();
+    let file = prepare_file(home.path(), "a.rs", "fn main() { let x = 1; }\n");
+    let with_theme = |theme: &str| {
+        stdout(
+            rucat(&home)
+                .args(["-f", "pretty", "--theme", theme])
+                .arg(&file),
+        )
+    };
+    let light = with_theme("InspiredGitHub");
+    let dark = with_theme("base16-ocean.dark");
+    assert_ne!(light, dark);
+
+    let auto = |colorfgbg: Option<&str>| {
+        let mut cmd = rucat(&home);
+        if let Some(value) = colorfgbg {
+            cmd.env("COLORFGBG", value);
+        }
+        stdout(cmd.args(["-f", "pretty"]).arg(&file))
+    };
+    assert_eq!(auto(Some("0;15")), light);
+    assert_eq!(auto(Some("15;0")), dark);
+    assert_eq!(auto(None), dark);
+
+    prepare_file(
+        home.path(),
+        &format!("{}/config.toml", config_dir()),
+        "theme_light = \"Solarized (light)\"\n",
+    );
+    assert_eq!(auto(Some("0;7")), with_theme("Solarized (light)"));
+}
+
+#[test]
+fn html_uses_the_theme() {
+    let home = tempdir().unwrap();
+    let file = prepare_file(home.path(), "a.rs", "fn main() {}\n");
+    let page = stdout(
+        rucat(&home)
+            .args(["-f", "html", "--theme", "InspiredGitHub"])
+            .arg(&file),
+    );
+    assert!(page.contains("background-color:#ffffff"));
+}
+
+#[test]
+fn loads_and_caches_custom_themes() {
+    let home = tempdir().unwrap();
+    let config = home.path().join(config_dir());
+    let theme_file = prepare_file(&config, "themes/Plain.tmTheme", &theme("#FF0000"));
+    let file = prepare_file(home.path(), "a.rs", "if x {}\n");
+
+    assert!(stdout(rucat(&home).arg("--list-themes")).contains("\nPlain\n"));
+    assert!(home.path().join(cache_dir()).join("themes.bin").exists());
+
+    let highlight = || {
+        stdout(
+            rucat(&home)
+                .args(["-f", "pretty", "--theme", "Plain"])
+                .arg(&file),
+        )
+    };
+    let red = highlight();
+    assert!(red.contains("\x1b[38;2;255;0;0mif"), "{red:?}");
+    // Served from the cache this time.
+    assert_eq!(highlight(), red);
+
+    // Changing a theme rebuilds the cache.
+    std::fs::write(&theme_file, theme("#0000FFFF")).unwrap();
+    assert!(highlight().contains("\x1b[38;2;0;0;255mif"));
+}
+
+// Building a syntax set takes minutes without optimisations, so this only
+// runs with `cargo test --release`.
+#[test]
+#[cfg_attr(debug_assertions, ignore = "slow in debug builds")]
+fn loads_and_caches_custom_syntaxes() {
+    let home = tempdir().unwrap();
+    let config = home.path().join(config_dir());
+    prepare_file(&config, "themes/Plain.tmTheme", &theme("#FF0000"));
+    let syntax_file = prepare_file(&config, "syntaxes/Nix.sublime-syntax", NIX_SYNTAX);
+    let file = prepare_file(home.path(), "a.nix", "let x = 1; in x\n");
+
+    assert!(stdout(rucat(&home).arg("--list-syntaxes")).contains("\nNix: nix\n"));
+    assert!(home.path().join(cache_dir()).join("syntaxes.bin").exists());
+
+    let highlight = || {
+        stdout(
+            rucat(&home)
+                .args(["-f", "pretty", "--theme", "Plain"])
+                .arg(&file),
+        )
+    };
+    let red = highlight();
+    assert!(red.contains("\x1b[38;2;255;0;0mlet"), "{red:?}");
+    // Served from the cache this time.
+    assert_eq!(highlight(), red);
+
+    // Changing a syntax rebuilds the cache.
+    std::fs::write(&syntax_file, NIX_SYNTAX.replace("let|", "")).unwrap();
+    assert!(!highlight().contains("\x1b[38;2;255;0;0mlet"));
+}
+
+#[test]
+fn broken_custom_files_are_skipped() {
+    let home = tempdir().unwrap();
+    let config = home.path().join(config_dir());
+    prepare_file(&config, "themes/Broken.tmTheme", "not a plist");
+    rucat(&home)
+        .arg("--list-themes")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("base16-ocean.dark")
+                .and(predicate::str::contains("Broken").not()),
+        )
+        .stderr(predicate::str::contains("Skipping theme"));
+}
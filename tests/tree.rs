@@ -0,0 +1,110 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use rucat::cli::TreeInfo;
+use rucat::tree::Tree;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn tree_lines_are_sorted_and_annotated() {
+    let files = [
+        (Path::new("src/main.rs"), "a\nb\n"),
+        (Path::new("src/formatters/xml.rs"), "x\n"),
+        (Path::new("README.md"), "r\n"),
+    ];
+    let tree = Tree::build(files, TreeInfo::Lines);
+    assert_eq!(
+        tree.lines(true),
+        vec![
+            "README.md (1 lines)",
+            "src/ (3 lines)",
+            "├── formatters/ (1 lines)",
+            "│   └── xml.rs (1 lines)",
+            "└── main.rs (2 lines)",
+        ]
+    );
+}
+
+#[test]
+fn single_child_directories_are_collapsed() {
+    let files = [(Path::new("a/b/c/d.txt"), "")];
+    let tree = Tree::build(files, TreeInfo::None);
+    assert_eq!(tree.lines(false), vec!["a/b/c/", "`-- d.txt"]);
+}
+
+#[test]
+fn cli_tree_markdown_is_fenced_before_contents() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/a.rs", "fn a() {}\n");
+    prepare_file(dir.path(), "src/b.rs", "fn b() {}\n");
+    let out = Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "markdown", "--tree", "src"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("```\nsrc/\n├── a.rs\n└── b.rs\n```\n"));
+    assert!(out.contains("fn a() {}"));
+}
+
+#[test]
+fn cli_tree_info_sizes_in_json() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/a.rs", "12345");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "json", "src", "--tree-info", "size"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("\"tree\": [")
+                .and(predicate::str::contains("\"size\": 5"))
+                .and(predicate::str::contains("\"files\": [")),
+        );
+}
+
+#[test]
+fn cli_tree_xml_and_utf8() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "src/a.rs", "x");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "xml", "--tree", "src"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "<tree>\n  <dir name=\"src\">\n    <file name=\"a.rs\"/>\n  </dir>\n</tree>",
+        ));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .current_dir(dir.path())
+        .args(["-f", "utf8", "--tree", "src"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("│ Tree ").and(predicate::str::contains("│└── a.rs")));
+}
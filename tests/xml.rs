@@ -0,0 +1,98 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use rucat::formatters::{Formatter, xml::Xml};
+use std::path::Path;
+use tempfile::tempdir;
+
+fn capture(fmt: &Xml, path: &str, content: &str) -> String {
+    let mut buf = Vec::new();
+    fmt.write(Path::new(path), content, &mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn path_attribute_is_escaped() {
+    let fmt = Xml {
+        line_numbers: false,
+        cdata: false,
+    };
+    assert_eq!(
+        capture(&fmt, "say \"hi\" & <bye>.txt", "x"),
+        "<file path=\"say &quot;hi&quot; &amp; &lt;bye&gt;.txt\">x</file>\n"
+    );
+}
+
+#[test]
+fn cdata_splits_end_markers() {
+    let fmt = Xml {
+        line_numbers: false,
+        cdata: true,
+    };
+    assert_eq!(
+        capture(&fmt, "a.rs", "if a[b[0]]>c && d < e {}"),
+        "<file path=\"a.rs\"><![CDATA[if a[b[0]]]]><![CDATA[>c && d < e {}]]></file>\n"
+    );
+}
+
+#[test]
+fn forbidden_control_characters_are_replaced() {
+    for cdata in [false, true] {
+        let fmt = Xml {
+            line_numbers: false,
+            cdata,
+        };
+        let out = capture(&fmt, "a\x07.txt", "\x1b[1mbold\x0c\tpage\n");
+        assert!(!out.contains(['\x07', '\x1b', '\x0c']), "{out:?}");
+        assert!(out.contains("\u{fffd}[1mbold\u{fffd}\tpage"), "{out:?}");
+        assert!(out.contains("path=\"a\u{fffd}.txt\""), "{out:?}");
+    }
+}
+
+#[test]
+fn cli_bundle_is_a_well_formed_document() {
+    let dir = tempdir().unwrap();
+    prepare_file(dir.path(), "a&b.txt", "x < y\n");
+    prepare_file(dir.path(), "q\"uote.txt", "]]> and &amp;\n");
+    prepare_file(dir.path(), "ctl.txt", "\x1b[31mred\x1b[0m\x0c\n");
+    for extra in [&[][..], &["--xml-cdata"][..], &["-n", "--tree"][..]] {
+        let output = Command::cargo_bin("rucat")
+            .unwrap()
+            .current_dir(dir.path())
+            .args(["-f", "xml", "a&b.txt", "q\"uote.txt", "ctl.txt"])
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let text = String::from_utf8(output.stdout).unwrap();
+        assert!(text.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<files>\n"));
+        let doc = roxmltree::Document::parse(&text).unwrap();
+        let files: Vec<_> = doc
+            .root_element()
+            .children()
+            .filter(|n| n.has_tag_name("file"))
+            .collect();
+        assert_eq!(doc.root_element().tag_name().name(), "files");
+        assert_eq!(files[0].attribute("path"), Some("a&b.txt"));
+        assert_eq!(files[1].attribute("path"), Some("q\"uote.txt"));
+        let body: String = files[1].descendants().filter_map(|n| n.text()).collect();
+        assert!(body.contains("]]> and &amp;"), "{extra:?}: {body:?}");
+    }
+}
@@ -0,0 +1,177 @@
+// This file is part of rucat.
+//
+// rucat is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// rucat is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with rucat.  If not, see <https://www.gnu.org/licenses/>.
+//
+// Copyright (C) 2024 Brian 'redbeard' Harrington
+mod common;
+
+use assert_cmd::Command;
+use common::prepare_file;
+use predicates::prelude::*;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+use yaml_rust::YamlLoader;
+
+fn run(format: &str, args: &[&str], files: &[&Path]) -> String {
+    let output = Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["-f", format])
+        .args(args)
+        .args(files)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Contents that are easy to get wrong in block scalars and multi-line strings.
+const AWKWARD: &[&str] = &[
+    "fn main() {\n    println!(\"hi\");\n}\n",
+    "no trailing newline\nat the end",
+    "  leading blanks\nx\n",
+    "blank lines at the end\n\n\n",
+    "\n\nblank lines at the start\n",
+    "tab\tand # not a comment: \"quoted\" 'single' \\ '''\"\"\"\n",
+    "windows\r\nline endings\r\n",
+    "single line",
+    "",
+];
+
+#[test]
+fn yaml_round_trips_contents() {
+    let dir = tempdir().unwrap();
+    let files: Vec<PathBuf> = AWKWARD
+        .iter()
+        .enumerate()
+        .map(|(i, body)| prepare_file(dir.path(), &format!("{i}.txt"), body))
+        .collect();
+    let paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    let out = run("yaml", &[], &paths);
+    let doc = &YamlLoader::load_from_str(&out).unwrap()[0];
+    assert_eq!(doc["version"].as_i64(), Some(1));
+    for (i, body) in AWKWARD.iter().enumerate() {
+        let file = &doc["files"][i];
+        assert_eq!(file["path"].as_str(), Some(files[i].to_str().unwrap()));
+        assert_eq!(file["content"].as_str(), Some(*body), "{out}");
+    }
+}
+
+#[test]
+fn yaml_writes_code_as_literal_blocks() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(
+        dir.path(),
+        "a.rs",
+        "fn main() {\n    println!(\"hi\");\n}\n",
+    );
+    let out = run("yaml", &[], &[&file]);
+    assert!(out.contains(&format!(
+        "files:\n  - path: \"{}\"\n    content: |\n      fn main() {{\n          println!(\"hi\");\n      }}\n",
+        file.display()
+    )));
+}
+
+#[test]
+fn toml_round_trips_contents() {
+    let dir = tempdir().unwrap();
+    let files: Vec<PathBuf> = AWKWARD
+        .iter()
+        .enumerate()
+        .map(|(i, body)| prepare_file(dir.path(), &format!("{i}.txt"), body))
+        .collect();
+    let paths: Vec<&Path> = files.iter().map(PathBuf::as_path).collect();
+    let out = run("toml", &["--prompt", "Review."], &paths);
+    let doc: toml::Table = out.parse().unwrap();
+    assert_eq!(doc["version"].as_integer(), Some(1));
+    assert_eq!(doc["prompt"].as_str(), Some("Review."));
+    for (i, body) in AWKWARD.iter().enumerate() {
+        assert_eq!(doc["files"][i]["content"].as_str(), Some(*body), "{out}");
+    }
+    assert!(out.contains("content = \"\"\"\nfn main() {\n"));
+}
+
+#[test]
+fn yaml_and_toml_carry_the_json_fields() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "src/a.py", "print(1)\n");
+    let missing = dir.path().join("missing.py");
+    let args = ["--tree", "--meta", "size,lines,lang,sha256"];
+    let files: [&Path; 2] = [&file, &missing];
+
+    let json: serde_json::Value = serde_json::from_str(&run("json", &args, &files)).unwrap();
+    let yaml = &YamlLoader::load_from_str(&run("yaml", &args, &files)).unwrap()[0];
+    let toml: toml::Table = run("toml", &args, &files).parse().unwrap();
+
+    let json_keys: Vec<&String> = json["files"][0].as_object().unwrap().keys().collect();
+    let yaml_keys: Vec<&str> = yaml["files"][0]
+        .as_hash()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str().unwrap())
+        .collect();
+    let toml_keys: Vec<&String> = toml["files"][0].as_table().unwrap().keys().collect();
+    assert_eq!(json_keys, yaml_keys);
+    let mut sorted = json_keys.clone();
+    sorted.sort();
+    assert_eq!(sorted, toml_keys);
+
+    let error = missing.to_str().unwrap();
+    assert_eq!(yaml["errors"][0]["path"].as_str(), Some(error));
+    assert_eq!(toml["errors"][0]["path"].as_str(), Some(error));
+    assert_eq!(yaml["files"][0]["lang"].as_str(), Some("Python"));
+    assert_eq!(
+        toml["files"][0]["sha256"].as_str(),
+        json["files"][0]["sha256"].as_str()
+    );
+    assert!(yaml["tree"][0]["name"].as_str().is_some());
+    assert_eq!(toml["stats"]["files"].as_integer(), Some(1));
+}
+
+#[test]
+fn split_parts_use_format_extensions() {
+    let dir = tempdir().unwrap();
+    let a = prepare_file(dir.path(), "a.txt", &"alpha\n".repeat(100));
+    let b = prepare_file(dir.path(), "b.txt", &"beta\n".repeat(100));
+    for (format, ext) in [("yaml", "part-1.yaml"), ("toml", "part-1.toml")] {
+        let out = dir.path().join(format);
+        Command::cargo_bin("rucat")
+            .unwrap()
+            .args(["-f", format, "--split", "300", "--split-dir"])
+            .arg(&out)
+            .arg(&a)
+            .arg(&b)
+            .assert()
+            .success();
+        assert!(out.join(ext).exists());
+    }
+}
+
+#[test]
+fn format_flag_accepts_yaml_and_toml() {
+    let dir = tempdir().unwrap();
+    let file = prepare_file(dir.path(), "a.txt", "one\n");
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .arg(&file)
+        .args(["-f", "toml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("version = 1\n"));
+    Command::cargo_bin("rucat")
+        .unwrap()
+        .args(["x.txt", "-f", "yml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("jsonl, yaml, toml, markdown"));
+}